@@ -0,0 +1,352 @@
+// Extended instruction grammar on top of the plain L/R rotations:
+//
+//   # comment until the end of the line
+//   L68 / R48         rotate the dial (as in the puzzle)
+//   =25               set the dial to an absolute position (does not count as reaching zero)
+//   x1000 { ... }     repeat the enclosed instructions
+//   def name { ... }  define a macro (top level only)
+//   @name             invoke a macro
+//
+// Blocks are not expanded. Instead, every block is compiled into a transition table that maps each start position to the
+// end position and the zero counts. Repetitions then use binary exponentiation of that table, so even x1000000000000 is fast.
+// Zero counts that do not fit into a u64 anymore are reported as error.
+
+use anyhow::{anyhow, bail, Result};
+use std::collections::{HashMap, HashSet};
+use std::fs::read_to_string;
+use std::path::Path;
+
+use crate::{parse_rotation, turn_dial, DIAL_SIZE};
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Stmt {
+    Rotate(i64),
+    Set(i64),
+    Repeat(u64, Vec<Stmt>),
+    Call { name: String, line: usize }
+}
+
+#[derive(Debug)]
+pub struct Program {
+    main: Vec<Stmt>,
+    macros: HashMap<String, Vec<Stmt>>
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Outcome {
+    pub final_pos: i64,
+    pub zero_hits: u64,  // first star rule
+    pub zero_passes: u64  // second star rule
+}
+
+type Count = Option<u64>;  // None if the count overflowed
+
+#[derive(Clone)]
+struct Transition {
+    // All vectors are indexed by the start position. Counts are tracked per start position since an overflow for one start
+    // position does not matter if the program is run from another one.
+    end: Vec<i64>,
+    hits: Vec<Count>,
+    passes: Vec<Count>
+}
+
+impl Transition {
+    fn identity() -> Self {
+        Transition {
+            end: (0..DIAL_SIZE).collect(),
+            hits: vec![Some(0); DIAL_SIZE as usize],
+            passes: vec![Some(0); DIAL_SIZE as usize]
+        }
+    }
+
+    fn rotation(rot: i64) -> Self {
+        let turns: Vec<_> = (0..DIAL_SIZE).map(|p| turn_dial(p, rot, DIAL_SIZE)).collect();
+        Transition {
+            end: turns.iter().map(|t| t.pos).collect(),
+            hits: turns.iter().map(|t| Some(t.zero_count(false))).collect(),
+            passes: turns.iter().map(|t| Some(t.zero_count(true))).collect()
+        }
+    }
+
+    fn set(pos: i64) -> Self {
+        Transition {
+            end: vec![pos; DIAL_SIZE as usize],
+            hits: vec![Some(0); DIAL_SIZE as usize],
+            passes: vec![Some(0); DIAL_SIZE as usize]
+        }
+    }
+
+    fn then(&self, other: &Transition) -> Transition {
+        let mut res = Transition::identity();
+        for p in 0..DIAL_SIZE as usize {
+            let q = self.end[p] as usize;
+            res.end[p] = other.end[q];
+            res.hits[p] = self.hits[p].zip(other.hits[q]).and_then(|(a, b)| a.checked_add(b));
+            res.passes[p] = self.passes[p].zip(other.passes[q]).and_then(|(a, b)| a.checked_add(b));
+        }
+        res
+    }
+
+    fn pow(&self, mut count: u64) -> Transition {
+        let mut res = Transition::identity();
+        let mut base = self.clone();
+
+        while count > 0 {
+            if count & 1 == 1 {
+                res = res.then(&base);
+            }
+            count >>= 1;
+            if count > 0 {
+                base = base.then(&base);
+            }
+        }
+
+        res
+    }
+}
+
+impl Program {
+    pub fn run(&self, start_pos: i64) -> Result<Outcome> {
+        // Macro calls have been validated while parsing, so compiling cannot fail here (only the counts can overflow)
+        let mut cache = HashMap::new();
+        let transition = self.compile(&self.main, &mut cache);
+        let start = start_pos.rem_euclid(DIAL_SIZE) as usize;
+
+        let (Some(zero_hits), Some(zero_passes)) = (transition.hits[start], transition.passes[start]) else {
+            bail!("Zero count does not fit into 64 bits!");
+        };
+        Ok(Outcome { final_pos: transition.end[start], zero_hits, zero_passes })
+    }
+
+    fn compile(&self, stmts: &[Stmt], cache: &mut HashMap<String, Transition>) -> Transition {
+        let mut res = Transition::identity();
+
+        for stmt in stmts {
+            let t = match stmt {
+                Stmt::Rotate(rot) => Transition::rotation(*rot),
+                Stmt::Set(pos) => Transition::set(*pos),
+                Stmt::Repeat(count, body) => self.compile(body, cache).pow(*count),
+                Stmt::Call { name, .. } => {
+                    if !cache.contains_key(name) {
+                        let t = self.compile(&self.macros[name], cache);
+                        cache.insert(name.clone(), t);
+                    }
+                    cache[name].clone()
+                }
+            };
+            res = res.then(&t);
+        }
+
+        res
+    }
+
+    fn check_calls<'a>(&'a self, stmts: &'a [Stmt], stack: &mut Vec<&'a str>, checked: &mut HashSet<&'a str>) -> Result<()> {
+        // Makes sure that all invoked macros exist and that there is no recursion (which could never terminate)
+        for stmt in stmts {
+            match stmt {
+                Stmt::Repeat(_, body) => self.check_calls(body, stack, checked)?,
+                Stmt::Call { name, line } => {
+                    let Some(body) = self.macros.get(name) else { bail!("Line {}: undefined macro '{}'", line, name) };
+                    if stack.contains(&name.as_str()) {
+                        bail!("Line {}: recursive invocation of macro '{}'", line, name);
+                    }
+                    if checked.contains(name.as_str()) {
+                        continue;
+                    }
+                    stack.push(name);
+                    self.check_calls(body, stack, checked)?;
+                    stack.pop();
+                    checked.insert(name);
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+}
+
+struct Parser<'a> {
+    tokens: Vec<(usize, &'a str)>,  // line number (starting at 1) and token
+    idx: usize
+}
+
+impl<'a> Parser<'a> {
+    fn new(input: &'a str) -> Self {
+        // Braces are tokens on their own, everything else is separated by whitespace
+        let mut tokens = vec!();
+        for (line_idx, line) in input.lines().enumerate() {
+            let line = line.split_once('#').map_or(line, |(code, _)| code);
+            for word in line.split_whitespace() {
+                let mut rest = word;
+                while let Some(brace_idx) = rest.find(['{', '}']) {
+                    if brace_idx > 0 {
+                        tokens.push((line_idx + 1, &rest[..brace_idx]));
+                    }
+                    tokens.push((line_idx + 1, &rest[brace_idx..brace_idx+1]));
+                    rest = &rest[brace_idx+1..];
+                }
+                if !rest.is_empty() {
+                    tokens.push((line_idx + 1, rest));
+                }
+            }
+        }
+
+        Parser { tokens, idx: 0 }
+    }
+
+    fn next(&mut self) -> Option<(usize, &'a str)> {
+        let res = self.tokens.get(self.idx).copied();
+        self.idx += 1;
+        res
+    }
+
+    fn expect_open_brace(&mut self, line: usize) -> Result<()> {
+        match self.next() {
+            Some((_, "{")) => Ok(()),
+            Some((l, token)) => bail!("Line {}: expected '{{' but found '{}'", l, token),
+            None => bail!("Line {}: expected '{{' but input ended", line)
+        }
+    }
+
+    fn parse_block(&mut self, open_line: Option<usize>, macros: &mut HashMap<String, Vec<Stmt>>) -> Result<Vec<Stmt>> {
+        // Parses statements until the closing brace (if open_line is set) or the end of the input (top level)
+        let mut res = vec!();
+
+        loop {
+            let Some((line, token)) = self.next() else {
+                match open_line {
+                    Some(l) => bail!("Line {}: block is never closed", l),
+                    None => return Ok(res)
+                }
+            };
+
+            if token == "}" {
+                if open_line.is_none() {
+                    bail!("Line {}: unmatched '}}'", line);
+                }
+                return Ok(res);
+            } else if token == "def" {
+                if open_line.is_some() {
+                    bail!("Line {}: macros can only be defined at the top level", line);
+                }
+                let name = match self.next() {
+                    Some((_, name)) if is_identifier(name) => name,
+                    _ => bail!("Line {}: expected macro name after 'def'", line)
+                };
+                if macros.contains_key(name) {
+                    bail!("Line {}: macro '{}' is defined twice", line, name);
+                }
+                self.expect_open_brace(line)?;
+                let body = self.parse_block(Some(line), macros)?;
+                macros.insert(name.to_string(), body);
+            } else if let Some(name) = token.strip_prefix('@') {
+                if !is_identifier(name) {
+                    bail!("Line {}: invalid macro name '{}'", line, name);
+                }
+                res.push(Stmt::Call { name: name.to_string(), line });
+            } else if let Some(pos_str) = token.strip_prefix('=') {
+                let pos: i64 = pos_str.parse().map_err(|e| anyhow!("Line {}: invalid position '{}': {}", line, pos_str, e))?;
+                if !(0..DIAL_SIZE).contains(&pos) {
+                    bail!("Line {}: position {} is outside of the dial", line, pos);
+                }
+                res.push(Stmt::Set(pos));
+            } else if let Some(count_str) = token.strip_prefix('x') {
+                let count: u64 = count_str.parse().map_err(|e| anyhow!("Line {}: invalid repeat count '{}': {}", line, count_str, e))?;
+                self.expect_open_brace(line)?;
+                let body = self.parse_block(Some(line), macros)?;
+                res.push(Stmt::Repeat(count, body));
+            } else {
+                let rot = parse_rotation(token).map_err(|e| anyhow!("Line {}: invalid instruction '{}': {}", line, token, e))?;
+                res.push(Stmt::Rotate(rot as i64));
+            }
+        }
+    }
+}
+
+fn is_identifier(s: &str) -> bool {
+    !s.is_empty() && s.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+pub fn parse_program(input: &str) -> Result<Program> {
+    let mut macros = HashMap::new();
+    let main = Parser::new(input).parse_block(None, &mut macros)?;
+    let program = Program { main, macros };
+
+    // Macros are checked in alphabetical order so that errors are deterministic
+    let mut checked = HashSet::new();
+    program.check_calls(&program.main, &mut vec!(), &mut checked)?;
+    let mut names: Vec<_> = program.macros.keys().collect();
+    names.sort();
+    for name in names {
+        program.check_calls(&program.macros[name], &mut vec!(name.as_str()), &mut checked)?;
+    }
+
+    Ok(program)
+}
+
+pub fn read_program_file<P: AsRef<Path>>(input_path: P) -> Result<Program> {
+    parse_program(&read_to_string(input_path)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{process_input, read_input_file, START_POS};
+
+    #[test]
+    fn plain_rotations_match_day1() {
+        let program = read_program_file("../inputs/day1_example.txt").unwrap();
+        let outcome = program.run(START_POS).unwrap();
+        assert_eq!(outcome.zero_hits, 3);
+        assert_eq!(outcome.zero_passes, 6);
+    }
+
+    #[test]
+    fn repeat_matches_expansion() {
+        let input = read_input_file("../inputs/day1_example.txt").unwrap();
+        let body: Vec<String> = input.iter().map(|r| if *r < 0 { format!("L{}", -r) } else { format!("R{}", r) }).collect();
+        let program = parse_program(&format!("# Example repeated\nx7 {{\n{}\n}}\n\nR1", body.join("\n"))).unwrap();
+
+        let mut expanded = input.repeat(7);
+        expanded.push(1);
+        let outcome = program.run(START_POS).unwrap();
+        assert_eq!(outcome.zero_hits, process_input(&expanded, false));
+        assert_eq!(outcome.zero_passes, process_input(&expanded, true));
+    }
+
+    #[test]
+    fn macros_set_and_huge_repeats() {
+        let program = parse_program("def spin { R100 }  # one full revolution\n=0\nx1000000000000 { @spin L1 R1 }").unwrap();
+        let outcome = program.run(START_POS).unwrap();
+        assert_eq!(outcome.final_pos, 0);
+        assert_eq!(outcome.zero_hits, 2_000_000_000_000);
+        assert_eq!(outcome.zero_passes, 2_000_000_000_000);
+    }
+
+    #[test]
+    fn huge_counts() {
+        // 2^63 full revolutions still fit, twice as many do not
+        let outcome = parse_program("=0\nx9223372036854775808 { R100 }").unwrap().run(0).unwrap();
+        assert_eq!((outcome.final_pos, outcome.zero_hits, outcome.zero_passes), (0, 1 << 63, 1 << 63));
+        let outcome = parse_program("x18446744073709551615 { R100 }").unwrap().run(0).unwrap();
+        assert_eq!(outcome.zero_passes, u64::MAX);
+
+        let err = parse_program("x9223372036854775808 { R100 R100 }").unwrap().run(0).unwrap_err();
+        assert_eq!(err.to_string(), "Zero count does not fit into 64 bits!");
+    }
+
+    #[test]
+    fn errors_point_at_line() {
+        let check = |input: &str, expected: &str| {
+            let err = parse_program(input).unwrap_err().to_string();
+            assert!(err.starts_with(expected), "unexpected error: {}", err);
+        };
+
+        check("L10\nX5\nR3", "Line 2:");
+        check("L10\nx3 {\nR1", "Line 2: block is never closed");
+        check("R1\n}", "Line 2: unmatched");
+        check("\n\n@foo", "Line 3: undefined macro 'foo'");
+        check("def a { @b }\ndef b { @a }\n", "Line 2: recursive invocation of macro 'a'");
+        check("=100", "Line 1: position 100");
+    }
+}
@@ -0,0 +1,126 @@
+// Reverse playback of a rotation list and zero counts for arbitrary windows of it. The window queries refer to the actual
+// trajectory of the dial, i.e., rotation i starts where the dial ended after rotation i-1.
+
+use crate::{turn_dial, DIAL_SIZE};
+
+pub fn undo_rotation(pos: i64, rot: i32) -> i64 {
+    (pos - rot as i64).rem_euclid(DIAL_SIZE)
+}
+
+pub fn replay_backwards(input: &[i32], final_pos: i64) -> Vec<i64> {
+    // Returns the positions from the final one back to the start position (n+1 entries)
+    let mut res = Vec::with_capacity(input.len() + 1);
+    let mut cur = final_pos.rem_euclid(DIAL_SIZE);
+    res.push(cur);
+
+    for rot in input.iter().rev() {
+        cur = undo_rotation(cur, *rot);
+        res.push(cur);
+    }
+
+    res
+}
+
+pub fn recover_start_position(input: &[i32], final_pos: i64) -> i64 {
+    let total: i64 = input.iter().map(|r| *r as i64).sum();
+    (final_pos - total).rem_euclid(DIAL_SIZE)
+}
+
+pub struct History {
+    positions: Vec<i64>,  // positions[i] is the position before rotation i (and positions[n] is the final position)
+    hits_prefix: Vec<u64>,  // number of zero hits (first star rule) of the first i rotations
+    passes_prefix: Vec<u64>  // same for the second star rule
+}
+
+impl History {
+    pub fn new(input: &[i32], start_pos: i64) -> Self {
+        let mut history = History {
+            positions: vec![start_pos.rem_euclid(DIAL_SIZE)],
+            hits_prefix: vec![0],
+            passes_prefix: vec![0]
+        };
+
+        for rot in input {
+            let turn = turn_dial(*history.positions.last().unwrap(), *rot as i64, DIAL_SIZE);
+            history.hits_prefix.push(history.hits_prefix.last().unwrap() + turn.zero_count(false));
+            history.passes_prefix.push(history.passes_prefix.last().unwrap() + turn.zero_count(true));
+            history.positions.push(turn.pos);
+        }
+
+        history
+    }
+
+    pub fn len(&self) -> usize {
+        self.positions.len() - 1
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn position_before(&self, idx: usize) -> i64 {
+        self.positions[idx]
+    }
+
+    pub fn final_position(&self) -> i64 {
+        *self.positions.last().unwrap()
+    }
+
+    pub fn zero_count(&self, start: usize, end: usize, count_zeroes_while_turning: bool) -> u64 {
+        // Zero count of the rotations in [start, end)
+        assert!(start <= end && end <= self.len(), "Invalid window!");
+        let prefix = if count_zeroes_while_turning { &self.passes_prefix } else { &self.hits_prefix };
+        prefix[end] - prefix[start]
+    }
+
+    pub fn best_window(&self, len: usize, count_zeroes_while_turning: bool) -> Option<(usize, u64)> {
+        // Returns the start index and zero count of the window with the given length that reaches zero most often (ties
+        // are resolved in favor of the earliest window)
+        if len > self.len() {
+            return None;
+        }
+
+        (0..=(self.len() - len))
+            .map(|start| (start, self.zero_count(start, start + len, count_zeroes_while_turning)))
+            .rev()
+            .max_by_key(|&(_, count)| count)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{process_input_from, read_input_file, START_POS};
+
+    #[test]
+    fn backwards_replay_recovers_start() {
+        let input = read_input_file("../inputs/day1_example.txt").unwrap();
+        let history = History::new(&input, START_POS);
+        assert_eq!(history.final_position(), 32);
+        assert!(!history.is_empty() && History::new(&[], START_POS).is_empty());
+
+        assert_eq!(recover_start_position(&input, 32), START_POS);
+        let mut backwards = replay_backwards(&input, 32);
+        backwards.reverse();
+        assert_eq!(backwards, (0..=history.len()).map(|i| history.position_before(i)).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn windows_match_simulation() {
+        let input = read_input_file("../inputs/day1_example.txt").unwrap();
+        let history = History::new(&input, START_POS);
+
+        for count_zeroes_while_turning in [false, true] {
+            for start in 0..=input.len() {
+                for end in start..=input.len() {
+                    let expected = process_input_from(&input[start..end], history.position_before(start), count_zeroes_while_turning);
+                    assert_eq!(history.zero_count(start, end, count_zeroes_while_turning), expected);
+                }
+            }
+        }
+
+        assert_eq!(history.best_window(input.len(), true), Some((0, 6)));
+        assert_eq!(history.best_window(2, true), Some((4, 2)));
+        assert_eq!(history.best_window(input.len() + 1, true), None);
+    }
+}
@@ -0,0 +1,91 @@
+use anyhow::{bail, Result};
+use std::fs::read_to_string;
+use std::path::Path;
+
+pub mod grammar;
+pub mod history;
+pub mod multi_dial;
+pub mod solver;
+pub mod stats;
+
+pub const DIAL_SIZE: i64 = 100;
+pub const START_POS: i64 = 50;
+
+struct Turn {
+    pos: i64,
+    passed_zero: u64,  // times the dial reached zero while turning, not counting the final position
+    wraps: i64  // signed number of full revolutions past the top of the dial (used for carries between wheels)
+}
+
+impl Turn {
+    fn zero_count(&self, count_zeroes_while_turning: bool) -> u64 {
+        let landed_on_zero = if self.pos == 0 { 1 } else { 0 };
+        if count_zeroes_while_turning { self.passed_zero + landed_on_zero } else { landed_on_zero }
+    }
+}
+
+fn turn_dial(pos: i64, rot: i64, size: i64) -> Turn {
+    // Rotates a dial (with positions 0..size) starting at pos, which is expected to be already normalized
+    let raw = pos + rot;
+    let end = raw.rem_euclid(size);
+
+    // Count multiples of size that are strictly passed, i.e., we count neither the start nor the end position
+    let passed_zero = if rot > 0 {
+        (raw - 1).div_euclid(size) - pos.div_euclid(size)
+    } else if rot < 0 {
+        (pos - 1).div_euclid(size) - raw.div_euclid(size)
+    } else {
+        0
+    };
+
+    Turn { pos: end, passed_zero: passed_zero as u64, wraps: raw.div_euclid(size) }
+}
+
+pub fn process_input(input: &[i32], count_zeroes_while_turning: bool) -> u64 {
+    process_input_from(input, START_POS, count_zeroes_while_turning)
+}
+
+fn process_input_from(input: &[i32], start_pos: i64, count_zeroes_while_turning: bool) -> u64 {
+    let mut zero_count = 0;
+    let mut cur = start_pos;
+
+    for rot in input {
+        let turn = turn_dial(cur, *rot as i64, DIAL_SIZE);
+        zero_count += turn.zero_count(count_zeroes_while_turning);
+        cur = turn.pos;
+    }
+
+    zero_count
+}
+
+fn parse_rotation(s: &str) -> Result<i32> {
+    let Some(prefix) = s.chars().next() else { bail!("Empty rotation!") };
+    let number: i32 = s[prefix.len_utf8()..].parse()?;
+    match prefix {
+        'L' => Ok(-number),
+        'R' => Ok(number),
+        _ => bail!("Unknown prefix: {}", s)
+    }
+}
+
+pub fn read_input_file<P: AsRef<Path>>(input_path: P) -> Result<Vec<i32>> {
+    let input = read_to_string(input_path)?;
+    input.lines().map(parse_rotation).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn example_first_star() {
+        let input = read_input_file("../inputs/day1_example.txt").unwrap();
+        assert_eq!(process_input(&input, false), 3);
+    }
+
+    #[test]
+    fn example_second_star() {
+        let input = read_input_file("../inputs/day1_example.txt").unwrap();
+        assert_eq!(process_input(&input, true), 6);
+    }
+}
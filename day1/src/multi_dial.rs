@@ -0,0 +1,183 @@
+// Multi-wheel combination lock: several dials that are geared together like an odometer. Whenever a wheel turns past
+// the top of its dial (from size-1 to 0 or vice versa), the next wheel is turned by its gearing ratio in the same direction.
+
+use anyhow::{bail, Result};
+use std::fs::read_to_string;
+use std::path::Path;
+
+use crate::{parse_rotation, turn_dial};
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Wheel {
+    pub size: i64,
+    pub pos: i64,
+    pub ratio: i64  // steps the next wheel is turned per full revolution of this wheel
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Target {
+    All,
+    Wheel(usize)
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Instruction {
+    pub target: Target,
+    pub rot: i64
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct WheelStats {
+    pub zero_hits: u64,  // times the wheel stopped at zero (first star rule)
+    pub zero_passes: u64  // times the wheel reached or passed zero (second star rule)
+}
+
+pub struct CombinationLock {
+    wheels: Vec<Wheel>,
+    stats: Vec<WheelStats>
+}
+
+impl CombinationLock {
+    pub fn new(wheels: Vec<Wheel>) -> Self {
+        assert!(wheels.iter().all(|w| w.size > 0), "Wheel size must be positive!");
+        let stats = vec![WheelStats::default(); wheels.len()];
+        let wheels = wheels.into_iter().map(|w| Wheel { pos: w.pos.rem_euclid(w.size), ..w }).collect();
+        CombinationLock { wheels, stats }
+    }
+
+    pub fn apply(&mut self, instruction: &Instruction) -> Result<()> {
+        match instruction.target {
+            Target::All => {
+                // Wheels are turned one after another starting with the first one, carries are propagated immediately
+                for idx in 0..self.wheels.len() {
+                    self.turn_wheel(idx, instruction.rot)?;
+                }
+            }
+            Target::Wheel(idx) => {
+                if idx >= self.wheels.len() {
+                    bail!("Lock has no wheel {} (only {} wheels)!", idx, self.wheels.len());
+                }
+                self.turn_wheel(idx, instruction.rot)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn apply_all(&mut self, instructions: &[Instruction]) -> Result<()> {
+        for instruction in instructions {
+            self.apply(instruction)?;
+        }
+        Ok(())
+    }
+
+    pub fn positions(&self) -> Vec<i64> {
+        self.wheels.iter().map(|w| w.pos).collect()
+    }
+
+    pub fn stats(&self) -> &[WheelStats] {
+        &self.stats
+    }
+
+    fn turn_wheel(&mut self, mut idx: usize, mut rot: i64) -> Result<()> {
+        // Carries that go beyond the last wheel are lost (like an odometer that overflows). With large gearing ratios,
+        // the carried rotation or the counts may not fit into 64 bits, which is reported as an error.
+        while rot != 0 && idx < self.wheels.len() {
+            let wheel = &mut self.wheels[idx];
+            if wheel.pos.checked_add(rot).is_none() {
+                bail!("Rotation of wheel {} does not fit into 64 bits!", idx);
+            }
+            let turn = turn_dial(wheel.pos, rot, wheel.size);
+            wheel.pos = turn.pos;
+
+            let stats = &mut self.stats[idx];
+            let (Some(zero_hits), Some(zero_passes)) = (
+                stats.zero_hits.checked_add(turn.zero_count(false)),
+                stats.zero_passes.checked_add(turn.zero_count(true))
+            ) else {
+                bail!("Zero count of wheel {} does not fit into 64 bits!", idx);
+            };
+            stats.zero_hits = zero_hits;
+            stats.zero_passes = zero_passes;
+
+            let Some(carry) = turn.wraps.checked_mul(wheel.ratio) else {
+                bail!("Carry from wheel {} does not fit into 64 bits!", idx);
+            };
+            rot = carry;
+            idx += 1;
+        }
+
+        Ok(())
+    }
+}
+
+fn parse_instruction(s: &str) -> Result<Instruction> {
+    // Syntax is either "L68" (all wheels) or "<wheel index>:L68" (single wheel), with "*:L68" being an explicit form of the former
+    let (target, rot_str) = match s.split_once(':') {
+        Some(("*", rot_str)) => (Target::All, rot_str),
+        Some((idx_str, rot_str)) => (Target::Wheel(idx_str.trim().parse()?), rot_str),
+        None => (Target::All, s)
+    };
+
+    Ok(Instruction { target, rot: parse_rotation(rot_str.trim())? as i64 })
+}
+
+pub fn read_multi_dial_file<P: AsRef<Path>>(input_path: P) -> Result<Vec<Instruction>> {
+    let input = read_to_string(input_path)?;
+    input.lines().map(parse_instruction).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::read_input_file;
+
+    #[test]
+    fn single_wheel_matches_day1() {
+        let input = read_input_file("../inputs/day1_example.txt").unwrap();
+        let instructions = read_multi_dial_file("../inputs/day1_example.txt").unwrap();
+        assert_eq!(instructions, input.iter().map(|r| Instruction { target: Target::All, rot: *r as i64 }).collect::<Vec<_>>());
+
+        let mut lock = CombinationLock::new(vec![Wheel { size: 100, pos: 50, ratio: 1 }]);
+        lock.apply_all(&instructions).unwrap();
+        assert_eq!(lock.stats(), &[WheelStats { zero_hits: 3, zero_passes: 6 }]);
+    }
+
+    #[test]
+    fn odometer_carries() {
+        let wheels = vec![
+            Wheel { size: 10, pos: 5, ratio: 1 },
+            Wheel { size: 10, pos: 9, ratio: 2 },
+            Wheel { size: 4, pos: 0, ratio: 1 }
+        ];
+        let mut lock = CombinationLock::new(wheels);
+
+        // Wheel 0 wraps twice -> wheel 1 turns by 2 and wraps once (landing on 1) -> wheel 2 turns by 2
+        lock.apply(&parse_instruction("0:R17").unwrap()).unwrap();
+        assert_eq!(lock.positions(), vec![2, 1, 2]);
+        assert_eq!(lock.stats()[0], WheelStats { zero_hits: 0, zero_passes: 2 });
+        assert_eq!(lock.stats()[1], WheelStats { zero_hits: 0, zero_passes: 1 });
+
+        // Borrow when turning left past zero
+        lock.apply(&parse_instruction("0:L3").unwrap()).unwrap();
+        assert_eq!(lock.positions(), vec![9, 0, 2]);
+        assert_eq!(lock.stats()[1], WheelStats { zero_hits: 1, zero_passes: 2 });
+
+        lock.apply(&parse_instruction("R1").unwrap()).unwrap();
+        assert_eq!(lock.positions(), vec![0, 2, 3]);
+
+        assert!(lock.apply(&parse_instruction("3:R1").unwrap()).is_err());
+    }
+
+    #[test]
+    fn large_gearing_ratios() {
+        // Wheel 0 wraps 100 times, so the single-position wheel 1 passes zero 10^10 times
+        let mut lock = CombinationLock::new(vec![Wheel { size: 10, pos: 0, ratio: 100_000_000 }, Wheel { size: 1, pos: 0, ratio: 1 }]);
+        lock.apply(&parse_instruction("0:R1000").unwrap()).unwrap();
+        assert_eq!(lock.stats()[1], WheelStats { zero_hits: 1, zero_passes: 10_000_000_000 });
+
+        // Carries beyond 64 bits are errors instead of wrong counts
+        let mut lock = CombinationLock::new(vec![Wheel { size: 10, pos: 0, ratio: i64::MAX / 2 }, Wheel { size: 10, pos: 0, ratio: 1 }]);
+        assert!(lock.apply(&parse_instruction("0:R30").unwrap()).is_err());
+    }
+}
@@ -0,0 +1,178 @@
+// Inverse questions on a rotation list: which start position yields the most/fewest zeros and how many rotations need to
+// have their direction flipped to reach a given zero count.
+
+use crate::{turn_dial, DIAL_SIZE, START_POS};
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Objective {
+    Maximize,
+    Minimize
+}
+
+fn add_floor_term(diff: &mut [i64], c: i64, sign: i64) {
+    // Adds sign * floor((s + c) / DIAL_SIZE) for all start positions s to the difference array. Within 0..DIAL_SIZE, this is a
+    // constant plus one step where s + c reaches the next multiple of the dial size.
+    let q = c.div_euclid(DIAL_SIZE);
+    let m = c.rem_euclid(DIAL_SIZE);
+
+    diff[0] += sign * q;
+    if m > 0 {
+        diff[(DIAL_SIZE - m) as usize] += sign;
+    }
+}
+
+pub fn zero_counts_for_all_starts(input: &[i32], count_zeroes_while_turning: bool) -> Vec<u32> {
+    // Instead of simulating every start position, we work with the unnormalized position a relative to the start. For a
+    // given start s, the zero count of a single rotation is a difference of two floor terms in s, which we accumulate in a
+    // difference array over all start positions. This takes O(n + dial size) instead of O(n * dial size).
+    let mut diff = vec![0i64; DIAL_SIZE as usize + 1];
+    let mut a = 0i64;
+
+    for rot in input {
+        let r = *rot as i64;
+
+        if count_zeroes_while_turning && r > 0 {
+            // Multiples of the dial size in [s + a + 1, s + a + r]
+            add_floor_term(&mut diff, a + r, 1);
+            add_floor_term(&mut diff, a, -1);
+        } else if count_zeroes_while_turning && r < 0 {
+            // Multiples of the dial size in [s + a + r, s + a - 1]
+            add_floor_term(&mut diff, a - 1, 1);
+            add_floor_term(&mut diff, a + r - 1, -1);
+        } else {
+            // Only the final position counts: s + a + r must be a multiple of the dial size
+            let s = (-(a + r)).rem_euclid(DIAL_SIZE) as usize;
+            diff[s] += 1;
+            diff[s + 1] -= 1;
+        }
+
+        a += r;
+    }
+
+    let mut res = Vec::with_capacity(DIAL_SIZE as usize);
+    let mut cur = 0;
+    for d in &diff[..DIAL_SIZE as usize] {
+        cur += d;
+        res.push(cur as u32);
+    }
+    res
+}
+
+pub fn best_start_position(input: &[i32], count_zeroes_while_turning: bool, objective: Objective) -> (i64, u32) {
+    // Returns the start position and the resulting zero count. For ties, the lowest start position wins.
+    let counts = zero_counts_for_all_starts(input, count_zeroes_while_turning);
+    let best = match objective {
+        Objective::Maximize => counts.iter().enumerate().rev().max_by_key(|&(_, c)| c),
+        Objective::Minimize => counts.iter().enumerate().min_by_key(|&(_, c)| c)
+    };
+    let (pos, count) = best.unwrap();
+    (pos as i64, *count)
+}
+
+pub fn min_flips_for_zero_count(input: &[i32], count_zeroes_while_turning: bool, target: u32) -> Option<usize> {
+    // Dynamic programming over (dial position, zero count so far) storing the minimal number of flips to reach that state.
+    // Zero counts only grow, so states exceeding the target can be dropped. This takes O(n * dial size * target), with the
+    // target being at most the largest reachable zero count (otherwise we return early).
+    const UNREACHABLE: u32 = u32::MAX;
+    let max_reachable: u64 = input
+        .iter()
+        .map(|rot| {
+            (0..DIAL_SIZE)
+                .flat_map(|pos| [*rot as i64, -(*rot as i64)].map(|r| turn_dial(pos, r, DIAL_SIZE).zero_count(count_zeroes_while_turning)))
+                .max()
+                .unwrap()
+        })
+        .sum();
+    if target as u64 > max_reachable {
+        return None;
+    }
+
+    let size = DIAL_SIZE as usize;
+    let counts = target as usize + 1;
+
+    let mut dp = vec![UNREACHABLE; size * counts];
+    let mut next = vec![UNREACHABLE; size * counts];
+    dp[START_POS as usize * counts] = 0;
+
+    for rot in input {
+        let r = *rot as i64;
+        next.fill(UNREACHABLE);
+
+        for pos in 0..size {
+            // Options are keeping the rotation as it is (no additional flip) or flipping it (a no-op for zero rotations,
+            // which is never better than not flipping)
+            let options = [(turn_dial(pos as i64, r, DIAL_SIZE), 0), (turn_dial(pos as i64, -r, DIAL_SIZE), 1)];
+
+            for cnt in 0..counts {
+                let flips = dp[pos * counts + cnt];
+                if flips == UNREACHABLE {
+                    continue;
+                }
+
+                for (turn, additional_flips) in &options {
+                    let new_cnt = cnt + turn.zero_count(count_zeroes_while_turning) as usize;
+                    if new_cnt < counts {
+                        let entry = &mut next[turn.pos as usize * counts + new_cnt];
+                        *entry = (*entry).min(flips + additional_flips);
+                    }
+                }
+            }
+        }
+
+        std::mem::swap(&mut dp, &mut next);
+    }
+
+    (0..size)
+        .map(|pos| dp[pos * counts + target as usize])
+        .filter(|f| *f != UNREACHABLE)
+        .min()
+        .map(|f| f as usize)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{process_input, process_input_from, read_input_file};
+
+    #[test]
+    fn all_starts_match_simulation() {
+        let input = read_input_file("../inputs/day1_example.txt").unwrap();
+
+        for count_zeroes_while_turning in [false, true] {
+            let counts = zero_counts_for_all_starts(&input, count_zeroes_while_turning);
+            for (start, count) in counts.iter().enumerate() {
+                assert_eq!(*count as u64, process_input_from(&input, start as i64, count_zeroes_while_turning));
+            }
+            assert_eq!(counts[START_POS as usize] as u64, process_input(&input, count_zeroes_while_turning));
+        }
+
+        let (_, max_count) = best_start_position(&input, true, Objective::Maximize);
+        assert!(max_count >= 6);
+        let (min_pos, min_count) = best_start_position(&input, true, Objective::Minimize);
+        assert_eq!(min_count, *zero_counts_for_all_starts(&input, true).iter().min().unwrap());
+        assert_eq!(process_input_from(&input, min_pos, true), min_count as u64);
+    }
+
+    #[test]
+    fn min_flips_match_brute_force() {
+        let input = read_input_file("../inputs/day1_example.txt").unwrap();
+
+        for count_zeroes_while_turning in [false, true] {
+            // Try all 2^n flip combinations and remember the minimal flip count per resulting zero count
+            let mut brute_force = vec![None; 50];
+            for mask in 0u32..(1 << input.len()) {
+                let flipped: Vec<i32> = input.iter().enumerate().map(|(i, r)| if mask & (1 << i) != 0 { -r } else { *r }).collect();
+                let zero_count = process_input(&flipped, count_zeroes_while_turning) as usize;
+                let flips = mask.count_ones() as usize;
+                brute_force[zero_count] = Some(brute_force[zero_count].map_or(flips, |f: usize| f.min(flips)));
+            }
+
+            for (target, expected) in brute_force.iter().enumerate() {
+                assert_eq!(min_flips_for_zero_count(&input, count_zeroes_while_turning, target as u32), *expected);
+            }
+        }
+
+        // Targets above the largest reachable count are rejected without building the state table
+        assert_eq!(min_flips_for_zero_count(&input, true, u32::MAX), None);
+    }
+}
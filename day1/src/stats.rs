@@ -0,0 +1,135 @@
+// Statistics about the dial history that go beyond the zero counts, computed in a single pass over the rotation list.
+
+use std::fmt;
+
+use crate::{turn_dial, DIAL_SIZE};
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DialStats {
+    pub landings: Vec<u32>,  // indexed by dial position, number of rotations that ended there
+    pub dwell: Vec<u64>,  // indexed by dial position, number of clicks after which the dial was at that position
+    pub total_distance: u64,  // sum of all clicks in both directions
+    pub winding_number: i64,  // net number of full revolutions (positive is clockwise, i.e., to the right)
+    pub zero_hits: u64,
+    pub zero_passes: u64,
+    pub longest_zero_free_start: usize,  // index of the first rotation of the longest stretch that never touched zero
+    pub longest_zero_free_len: usize
+}
+
+impl DialStats {
+        pub fn collect(input: &[i32], start_pos: i64) -> Self {
+        let mut stats = DialStats {
+            landings: vec![0; DIAL_SIZE as usize],
+            dwell: vec![0; DIAL_SIZE as usize],
+            total_distance: 0,
+            winding_number: 0,
+            zero_hits: 0,
+            zero_passes: 0,
+            longest_zero_free_start: 0,
+            longest_zero_free_len: 0
+        };
+
+        let mut cur = start_pos;
+        let mut zero_free_start = 0;
+        let mut full_revolutions = 0;  // every position gets one click per full revolution, added at the end
+
+        for (idx, rot) in input.iter().enumerate() {
+            let turn = turn_dial(cur, *rot as i64, DIAL_SIZE);
+            full_revolutions += rot.unsigned_abs() as u64 / DIAL_SIZE as u64;
+            for step in 1..=(rot.unsigned_abs() as i64 % DIAL_SIZE) {
+                stats.dwell[(cur + rot.signum() as i64 * step).rem_euclid(DIAL_SIZE) as usize] += 1;
+            }
+            cur = turn.pos;
+
+            stats.landings[cur as usize] += 1;
+            stats.total_distance += rot.unsigned_abs() as u64;
+            stats.winding_number += turn.wraps;
+            stats.zero_hits += turn.zero_count(false);
+            stats.zero_passes += turn.zero_count(true);
+
+            // A rotation touches zero if it passes or stops at zero
+            if turn.zero_count(true) > 0 {
+                zero_free_start = idx + 1;
+            } else if idx + 1 - zero_free_start > stats.longest_zero_free_len {
+                stats.longest_zero_free_start = zero_free_start;
+                stats.longest_zero_free_len = idx + 1 - zero_free_start;
+            }
+        }
+
+        stats.dwell.iter_mut().for_each(|d| *d += full_revolutions);
+        stats
+    }
+
+        pub fn to_json(&self) -> String {
+        let landings: Vec<String> = self.landings.iter().map(|l| l.to_string()).collect();
+        let dwell: Vec<String> = self.dwell.iter().map(|d| d.to_string()).collect();
+        format!(
+            "{{\"landings\":[{}],\"dwell\":[{}],\"total_distance\":{},\"winding_number\":{},\"zero_hits\":{},\"zero_passes\":{},\"longest_zero_free\":{{\"start\":{},\"len\":{}}}}}",
+            landings.join(","),
+            dwell.join(","),
+            self.total_distance,
+            self.winding_number,
+            self.zero_hits,
+            self.zero_passes,
+            self.longest_zero_free_start,
+            self.longest_zero_free_len
+        )
+    }
+}
+
+impl fmt::Display for DialStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Total distance: {}", self.total_distance)?;
+        writeln!(f, "Winding number: {}", self.winding_number)?;
+        writeln!(f, "Zero hits (first star rule): {}", self.zero_hits)?;
+        writeln!(f, "Zero hits or passes (second star rule): {}", self.zero_passes)?;
+        writeln!(f, "Longest stretch without touching zero: {} rotations starting at rotation {}", self.longest_zero_free_len, self.longest_zero_free_start)?;
+        writeln!(f, "Landings per position:")?;
+
+        // Only positions that were actually landed on are listed
+        for (pos, count) in self.landings.iter().enumerate().filter(|(_, c)| **c > 0) {
+            writeln!(f, "  {:>2}: {}", pos, count)?;
+        }
+
+        writeln!(f, "Dwell time per position (clicks):")?;
+        for (pos, clicks) in self.dwell.iter().enumerate().filter(|(_, c)| **c > 0) {
+            writeln!(f, "  {:>2}: {}", pos, clicks)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{read_input_file, START_POS};
+
+    #[test]
+    fn example_stats() {
+        let input = read_input_file("../inputs/day1_example.txt").unwrap();
+        let stats = DialStats::collect(&input, START_POS);
+
+        // Positions: 82, 52, 0, 95, 55, 0, 99, 0, 14, 32
+        assert_eq!(stats.landings.iter().sum::<u32>(), 10);
+        assert_eq!(stats.landings[0], 3);
+        assert_eq!(stats.landings[55], 1);
+        assert_eq!(stats.total_distance, 462);
+        assert_eq!(stats.winding_number, -2);
+        assert_eq!((stats.zero_hits, stats.zero_passes), (3, 6));
+        assert_eq!((stats.longest_zero_free_start, stats.longest_zero_free_len), (1, 1));
+
+        // Every click ends at some position, and the clicks ending at zero are exactly the second star count
+        assert_eq!(stats.dwell.iter().sum::<u64>(), stats.total_distance);
+        assert_eq!(stats.dwell[0], stats.zero_passes);
+        // Two full revolutions reach every position twice, the remaining 50 clicks end at 1 to 50
+        assert_eq!(DialStats::collect(&[250], 0).dwell[..3], [2, 3, 3]);
+
+        let json = stats.to_json();
+        assert!(json.starts_with("{\"landings\":[3,0,"));
+        assert!(json.contains(&format!("\"dwell\":[6,{},", stats.dwell[1])));
+        assert!(json.ends_with("\"longest_zero_free\":{\"start\":1,\"len\":1}}"));
+        assert!(stats.to_string().contains("  55: 1\n"));
+        assert!(stats.to_string().contains("Dwell time per position (clicks):\n   0: 6\n"));
+    }
+}
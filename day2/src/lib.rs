@@ -0,0 +1,426 @@
+use anyhow::{anyhow, bail, Result};
+use num_bigint::BigUint;
+use std::fs::read_to_string;
+use std::path::Path;
+
+pub mod report;
+
+pub type Id = u128;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct RepetitionRule {
+    min_repetitions: u32,
+    max_repetitions: Option<u32>,
+    min_unit_len: u32,
+    max_unit_len: Option<u32>,
+    primitive_unit: bool  // if set, the unit must not be periodic itself (e.g., 1111 is not accepted as 11 repeated twice)
+}
+
+impl RepetitionRule {
+    pub fn exactly(repetitions: u32) -> Self {
+        RepetitionRule { min_repetitions: repetitions, max_repetitions: Some(repetitions), min_unit_len: 1, max_unit_len: None, primitive_unit: false }
+    }
+
+    pub fn at_least(repetitions: u32) -> Self {
+        RepetitionRule { min_repetitions: repetitions, max_repetitions: None, min_unit_len: 1, max_unit_len: None, primitive_unit: false }
+    }
+
+    pub fn first_star() -> Self {
+        RepetitionRule::exactly(2)
+    }
+
+    pub fn second_star() -> Self {
+        RepetitionRule::at_least(2)
+    }
+
+    pub fn with_max_repetitions(self, max_repetitions: u32) -> Self {
+        RepetitionRule { max_repetitions: Some(max_repetitions), ..self }
+    }
+
+    pub fn with_unit_len(self, min_unit_len: u32, max_unit_len: u32) -> Self {
+        RepetitionRule { min_unit_len, max_unit_len: Some(max_unit_len), ..self }
+    }
+
+    pub fn with_primitive_unit(self) -> Self {
+        RepetitionRule { primitive_unit: true, ..self }
+    }
+
+    fn accepts_period(&self, len: u32, period: u32) -> bool {
+        let repetitions = len / period;
+        len.is_multiple_of(period)
+            && repetitions >= self.min_repetitions
+            && self.max_repetitions.is_none_or(|m| repetitions <= m)
+            && period >= self.min_unit_len
+            && self.max_unit_len.is_none_or(|m| period <= m)
+    }
+
+    fn reported_period(&self, len: u32, minimal_period: u32) -> Option<u32> {
+        // For a number with the given minimal period, returns the period under which the rule accepts it (if any). A
+        // number has all multiples of its minimal period (that divide len) as periods as well. If there are several
+        // candidates, we take the shortest unit.
+        if self.primitive_unit {
+            return Some(minimal_period).filter(|p| self.accepts_period(len, *p));
+        }
+        divisors(len).into_iter().find(|p| p.is_multiple_of(minimal_period) && self.accepts_period(len, *p))
+    }
+}
+
+fn digit_count(n: Id, base: u32) -> u32 {
+    n.checked_ilog(base as Id).unwrap_or(0) + 1
+}
+
+fn divisors(n: u32) -> Vec<u32> {
+    (1..=n).filter(|d| n.is_multiple_of(*d)).collect()
+}
+
+fn max_with_digits(len: u32, base: u32) -> Id {
+    // Largest number with len digits, i.e., base^len - 1 (or the largest ID if that does not fit)
+    (base as Id).checked_pow(len).map_or(Id::MAX, |p| p - 1)
+}
+
+fn repetition_multiplier(len: u32, period: u32, base: u32) -> Id {
+    // Every number with len digits that consists of a period-digit unit u repeated len/period times can be written as
+    // u * (base^(len-period) + ... + base^period + 1). Calculated as a sum since base^len itself might not fit into an ID.
+    (0..len / period).map(|k| (base as Id).pow(k * period)).sum()
+}
+
+fn unit_range(start: Id, end: Id, len: u32, period: u32, base: u32) -> Option<(Id, Id)> {
+    // Returns the first and last unit (with exactly period digits, i.e., no leading zero) whose repetition to a number with
+    // len digits falls into [start, end]
+    let multiplier = repetition_multiplier(len, period, base);
+    let lo = start.max((base as Id).pow(len - 1));
+    let hi = end.min(max_with_digits(len, base));
+    if lo > hi {
+        return None;
+    }
+
+    let first_unit = lo.div_ceil(multiplier).max((base as Id).pow(period - 1));
+    let last_unit = (hi / multiplier).min(max_with_digits(period, base));
+    if first_unit > last_unit {
+        return None;
+    }
+
+    Some((first_unit, last_unit))
+}
+
+fn sum_with_period(start: Id, end: Id, len: u32, period: u32, base: u32) -> BigUint {
+    // We only need to sum up all units u for which the repeated number falls into [start, end]. The sum is calculated with
+    // arbitrary precision since it can easily exceed 128 bits for long IDs.
+    match unit_range(start, end, len, period, base) {
+        Some((first_unit, last_unit)) => {
+            let unit_sum = (BigUint::from(first_unit) + last_unit) * (last_unit - first_unit + 1) / 2u32;
+            unit_sum * repetition_multiplier(len, period, base)
+        }
+        None => BigUint::ZERO
+    }
+}
+
+fn has_period(n: Id, len: u32, period: u32, base: u32) -> bool {
+    n == (n / (base as Id).pow(len - period)) * repetition_multiplier(len, period, base)
+}
+
+pub fn is_invalid_id(id: Id, rule: &RepetitionRule, base: u32) -> bool {
+    let len = digit_count(id, base);
+    let minimal_period = divisors(len).into_iter().find(|p| has_period(id, len, *p, base)).unwrap();
+    rule.reported_period(len, minimal_period).is_some()
+}
+
+fn sum_of_invalid_ids(start: Id, end: Id, rule: &RepetitionRule, base: u32) -> BigUint {
+    let mut res = BigUint::ZERO;
+
+    for len in digit_count(start, base)..=digit_count(end, base) {
+        // A number with period p also has all multiples of its minimal period d as periods, so summing up over all
+        // accepted periods would count numbers multiple times. Instead, we calculate (by inclusion-exclusion over the
+        // divisors) the sum of numbers whose minimal period is exactly d, and then add these up for every d for which the
+        // rule accepts the number.
+        let len_divisors = divisors(len);
+        let mut minimal_period_sums: Vec<BigUint> = vec![];
+
+        for (idx, d) in len_divisors.iter().enumerate() {
+            let smaller_periods_sum: BigUint = len_divisors[..idx]
+                .iter()
+                .zip(minimal_period_sums.iter())
+                .filter(|(e, _)| d % *e == 0)
+                .map(|(_, s)| s)
+                .sum();
+            minimal_period_sums.push(sum_with_period(start, end, len, *d, base) - smaller_periods_sum);
+
+            if rule.reported_period(len, *d).is_some() {
+                res += &minimal_period_sums[idx];
+            }
+        }
+    }
+
+    res
+}
+
+pub fn process_input(input: &[(Id, Id)], rule: &RepetitionRule) -> Result<Id> {
+    process_input_with_base(input, rule, 10)
+}
+
+pub fn process_input_with_base(input: &[(Id, Id)], rule: &RepetitionRule, base: u32) -> Result<Id> {
+    // Use process_input_big if the sum might not fit into 128 bits
+    let Ok(sum) = process_input_big(input, rule, base).try_into() else { bail!("Sum of invalid IDs does not fit into 128 bits!") };
+    Ok(sum)
+}
+
+pub fn process_input_big(input: &[(Id, Id)], rule: &RepetitionRule, base: u32) -> BigUint {
+    assert!((2..=36).contains(&base), "Base must be between 2 and 36!");
+    input
+        .iter()
+        .map(|(start, end)| sum_of_invalid_ids(*start, *end, rule, base))
+        .sum()
+}
+
+pub fn format_in_base(mut n: Id, base: u32) -> String {
+    let mut digits = vec!();
+    loop {
+        digits.push(char::from_digit((n % base as Id) as u32, base).unwrap());
+        n /= base as Id;
+        if n == 0 {
+            break;
+        }
+    }
+    digits.iter().rev().collect()
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum RangeMode {
+    Strict,  // reversed ranges (e.g., 60-10) are rejected
+    Lenient  // reversed ranges are swapped
+}
+
+fn normalize_ranges(ranges: &[(Id, Id)], mode: RangeMode) -> Result<Vec<(Id, Id)>> {
+    // Sorts the ranges and merges overlapping (or adjacent) ones, so that every ID is contained in at most one range
+    let mut sorted = Vec::with_capacity(ranges.len());
+    for (idx, (start, end)) in ranges.iter().enumerate() {
+        if start > end {
+            match mode {
+                RangeMode::Strict => bail!("Range #{} is reversed: {}-{}", idx + 1, start, end),
+                RangeMode::Lenient => sorted.push((*end, *start))
+            }
+        } else {
+            sorted.push((*start, *end));
+        }
+    }
+    sorted.sort();
+
+    let mut res: Vec<(Id, Id)> = vec!();
+    for (start, end) in sorted {
+        match res.last_mut() {
+            Some(last) if start <= last.1.saturating_add(1) => { last.1 = last.1.max(end); }
+            _ => { res.push((start, end)); }
+        }
+    }
+
+    Ok(res)
+}
+
+fn parse_ranges(input: &str, base: u32) -> Result<Vec<(Id, Id)>> {
+    // Ranges are separated by commas and/or whitespace (including line breaks). Range endpoints are written in the given
+    // base (digits above 9 as letters, case-insensitive).
+    let mut res: Vec<(Id, Id)> = vec!();
+
+    for pair_str in input.split(|c: char| c == ',' || c.is_whitespace()).filter(|s| !s.is_empty()) {
+        let components: Vec<&str> = pair_str.split("-").collect();
+        match components.as_slice() {
+            [start, end] => {
+                let parse = |s: &str| Id::from_str_radix(s, base).map_err(|e| anyhow!("Could not parse {}: {}", pair_str, e));
+                res.push((parse(start)?, parse(end)?));
+            }
+            _ => { bail!("Could not parse: {}", pair_str); }
+        }
+    }
+
+    Ok(res)
+}
+
+pub fn read_input_file<P: AsRef<Path>>(input_path: P) -> Result<Vec<(Id, Id)>> {
+    read_input_file_with_base(input_path, 10, RangeMode::Strict)
+}
+
+pub fn read_input_file_with_base<P: AsRef<Path>>(input_path: P, base: u32, mode: RangeMode) -> Result<Vec<(Id, Id)>> {
+    let input = read_to_string(input_path)?;
+    normalize_ranges(&parse_ranges(&input, base)?, mode)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn process_input_scanning(input: &[(Id, Id)], rule: &RepetitionRule) -> Id {
+        // Straightforward reference implementation that checks every single ID
+        let mut invalid_id_sum = 0;
+
+        for (start, end) in input {
+            for cur in *start..=*end {
+                let s = cur.to_string();
+                let len = s.len();
+                let has_unit = |unit_len: usize| len % unit_len == 0 && s == s[0..unit_len].repeat(len / unit_len);
+                let minimal_unit_len = (1..=len).find(|l| has_unit(*l)).unwrap();
+
+                let invalid = (1..=len).any(|unit_len| {
+                    has_unit(unit_len)
+                        && rule.accepts_period(len as u32, unit_len as u32)
+                        && (!rule.primitive_unit || unit_len == minimal_unit_len)
+                });
+
+                if invalid {
+                    invalid_id_sum += cur;
+                }
+            }
+        }
+
+        invalid_id_sum
+    }
+
+    #[test]
+    fn example_first_star() {
+        let input = read_input_file("../inputs/day2_example.txt").unwrap();
+        assert_eq!(process_input(&input, &RepetitionRule::first_star()).unwrap(), 1227775554);
+    }
+
+    #[test]
+    fn example_second_star() {
+        let input = read_input_file("../inputs/day2_example.txt").unwrap();
+        assert_eq!(process_input(&input, &RepetitionRule::second_star()).unwrap(), 4174379265);
+    }
+
+    #[test]
+    fn arithmetic_matches_scanning() {
+        let input = [(1, 12345), (95, 115), (100000, 123456), (9999990, 10000100), (1111111, 1111111), (0, 0)];
+        for rule in [RepetitionRule::first_star(), RepetitionRule::second_star()] {
+            for range in input {
+                assert_eq!(process_input(&[range], &rule).unwrap(), process_input_scanning(&[range], &rule));
+            }
+        }
+    }
+
+    #[test]
+    fn configurable_rules() {
+        let rules = [
+            RepetitionRule::exactly(3),
+            RepetitionRule::exactly(2).with_primitive_unit(),
+            RepetitionRule::at_least(3),
+            RepetitionRule::at_least(2).with_max_repetitions(3),
+            RepetitionRule::at_least(2).with_unit_len(2, 3),
+            RepetitionRule::at_least(2).with_unit_len(2, 3).with_primitive_unit(),
+            RepetitionRule::at_least(1).with_unit_len(4, 4)
+        ];
+        for rule in rules {
+            for range in [(1, 200000), (11111000, 11112000), (12121200, 12121300)] {
+                assert_eq!(process_input(&[range], &rule).unwrap(), process_input_scanning(&[range], &rule), "{:?} {:?}", rule, range);
+            }
+        }
+
+        // 1111 consists of 11 repeated twice, but 11 itself is periodic
+        assert_eq!(process_input(&[(1111, 1111)], &RepetitionRule::exactly(2)).unwrap(), 1111);
+        assert_eq!(process_input(&[(1111, 1111)], &RepetitionRule::exactly(2).with_primitive_unit()).unwrap(), 0);
+    }
+
+    #[test]
+    fn huge_ranges() {
+        // Sum of 11, 22, ..., 99 is 495, for four digits it is 101 * (10 + ... + 99) etc.
+        assert_eq!(process_input(&[(1, 99)], &RepetitionRule::first_star()).unwrap(), 495);
+        assert_eq!(process_input(&[(1, 9999)], &RepetitionRule::first_star()).unwrap(), 495 + 101 * 4905);
+
+        // 17 is prime, so only the nine repdigits with 17 digits are invalid IDs
+        let repdigits_sum = BigUint::from(11_111_111_111_111_111u64 * 45);
+        let rule = RepetitionRule::second_star();
+        assert_eq!(sum_of_invalid_ids(1, 99_999_999_999_999_999, &rule, 10) - sum_of_invalid_ids(1, 9_999_999_999_999_999, &rule, 10), repdigits_sum);
+        assert!(sum_of_invalid_ids(1, Id::MAX, &rule, 10) > sum_of_invalid_ids(1, Id::MAX, &RepetitionRule::first_star(), 10));
+    }
+
+    #[test]
+    fn thirty_digit_ids() {
+        // All 30-digit numbers consisting of two equal halves: (10^15 + 1) * (10^14 + ... + (10^15 - 1))
+        let first_unit = BigUint::from(10u64.pow(14));
+        let last_unit = BigUint::from(10u64.pow(15) - 1);
+        let expected = (BigUint::from(10u64.pow(15)) + 1u32) * (&first_unit + &last_unit) * (&last_unit - &first_unit + 1u32) / 2u32;
+        let all_30_digits = [(10u128.pow(29), 10u128.pow(30) - 1)];
+        assert_eq!(process_input_big(&all_30_digits, &RepetitionRule::first_star(), 10), expected);
+
+        // Narrow ranges still fit into 128 bits and can be cross-checked by scanning
+        let narrow = [(123456789012345123456789012340, 123456789012345123456789012350), (10u128.pow(38) - 5, 10u128.pow(38) + 5)];
+        for rule in [RepetitionRule::first_star(), RepetitionRule::second_star()] {
+            assert_eq!(process_input(&narrow[..1], &rule).unwrap(), 123456789012345123456789012345);
+            assert_eq!(process_input(&narrow[1..], &rule).unwrap(), process_input_scanning(&narrow[1..], &rule));
+        }
+
+        // Every ID is accepted by a single repetition, up to the largest representable ID
+        let expected: BigUint = (Id::MAX - 1000..=Id::MAX).map(BigUint::from).sum();
+        assert_eq!(process_input_big(&[(Id::MAX - 1000, Id::MAX)], &RepetitionRule::at_least(1), 10), expected);
+
+        // Sums beyond 128 bits are only available with arbitrary precision
+        let err = process_input(&[(1, Id::MAX)], &RepetitionRule::second_star()).unwrap_err();
+        assert_eq!(err.to_string(), "Sum of invalid IDs does not fit into 128 bits!");
+        assert!(process_input_big(&[(1, Id::MAX)], &RepetitionRule::second_star(), 10) > BigUint::from(Id::MAX));
+    }
+
+    #[test]
+    fn other_bases() {
+        // 0b1010 = 10, 0b1111 = 15, 0b101010 = 42, 0xABAB = 43947
+        assert!(is_invalid_id(10, &RepetitionRule::first_star(), 2));
+        assert!(is_invalid_id(15, &RepetitionRule::first_star(), 2));
+        assert!(!is_invalid_id(42, &RepetitionRule::first_star(), 2));
+        assert!(is_invalid_id(42, &RepetitionRule::second_star(), 2));
+        assert!(is_invalid_id(0xABAB, &RepetitionRule::first_star(), 16));
+        assert!(!is_invalid_id(0xABAB, &RepetitionRule::first_star(), 10));
+
+        for base in [2, 3, 8, 16, 36] {
+            for rule in [RepetitionRule::first_star(), RepetitionRule::second_star()] {
+                let expected: Id = (1..20000).filter(|id| is_invalid_id(*id, &rule, base)).sum();
+                assert_eq!(process_input_with_base(&[(1, 19999)], &rule, base).unwrap(), expected);
+
+                // Cross-check the arithmetic single ID check against the string representation
+                let ids_from_strings: Id = (1..20000)
+                    .filter(|id| {
+                        let s = format_in_base(*id, base);
+                        let len = s.len();
+                        let half_len = len / 2;
+                        if rule == RepetitionRule::first_star() {
+                            len.is_multiple_of(2) && s[..half_len] == s[half_len..]
+                        } else {
+                            (1..=half_len).any(|l| len.is_multiple_of(l) && s == s[..l].repeat(len / l))
+                        }
+                    })
+                    .sum();
+                assert_eq!(ids_from_strings, expected);
+            }
+        }
+    }
+
+    #[test]
+    fn parse_in_base() {
+        let input = read_input_file_with_base("../inputs/day2_example_hex.txt", 16, RangeMode::Strict).unwrap();
+        assert_eq!(input, vec![(0xA, 0xFF), (0xABAA, 0xABAC)]);
+        assert_eq!(process_input_with_base(&input, &RepetitionRule::first_star(), 16).unwrap(), (1..=15).map(|d| d * 0x11).sum::<Id>() + 0xABAB);
+        assert_eq!(format_in_base(0xABAB, 16), "abab");
+    }
+
+    #[test]
+    fn overlapping_and_reversed_ranges() {
+        let rule = RepetitionRule::second_star();
+        assert_eq!(normalize_ranges(&[(10, 50), (40, 60)], RangeMode::Strict).unwrap(), vec![(10, 60)]);
+        assert_eq!(normalize_ranges(&[(40, 60), (61, 70), (1, 5)], RangeMode::Strict).unwrap(), vec![(1, 5), (40, 70)]);
+        assert_eq!(normalize_ranges(&[(0, Id::MAX), (5, 7)], RangeMode::Strict).unwrap(), vec![(0, Id::MAX)]);
+
+        let err = normalize_ranges(&[(10, 50), (60, 10)], RangeMode::Strict).unwrap_err();
+        assert_eq!(err.to_string(), "Range #2 is reversed: 60-10");
+        let ranges = normalize_ranges(&[(10, 50), (60, 10)], RangeMode::Lenient).unwrap();
+        assert_eq!(ranges, vec![(10, 60)]);
+        assert_eq!(process_input(&ranges, &rule).unwrap(), 11 + 22 + 33 + 44 + 55);
+    }
+
+    #[test]
+    fn messy_input_format() {
+        // Same ranges as the example, but spread over several lines with whitespace and overlaps
+        let input = read_input_file("../inputs/day2_example_messy.txt").unwrap();
+        assert_eq!(input.len(), 11);
+        assert_eq!(process_input(&input, &RepetitionRule::first_star()).unwrap(), 1227775554);
+        assert_eq!(process_input(&input, &RepetitionRule::second_star()).unwrap(), 4174379265);
+
+        assert!(parse_ranges("11-22,,95", 10).is_err());
+        assert!(parse_ranges("11-x2", 10).unwrap_err().to_string().starts_with("Could not parse 11-x2"));
+    }
+}
@@ -0,0 +1,186 @@
+// Lists the individual invalid IDs (instead of only their sum) together with the reason why they are invalid. Like the
+// sum calculation, this does not scan the ranges but generates the repeated numbers directly, in ascending order.
+
+use anyhow::Result;
+use std::io::Write;
+
+use crate::{digit_count, divisors, format_in_base, has_period, repetition_multiplier, unit_range, Id, RepetitionRule};
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct InvalidId {
+    pub id: Id,
+    pub range: (Id, Id),
+    pub unit: Id,
+    pub repetitions: u32
+}
+
+struct PeriodCursor {
+    minimal_period: u32,
+    reported_period: u32,  // period under which the rule accepts numbers with this minimal period
+    multiplier: Id,
+    next_unit: Id,
+    last_unit: Id
+}
+
+pub struct InvalidIds<'a> {
+    ranges: std::slice::Iter<'a, (Id, Id)>,
+    rule: RepetitionRule,
+    base: u32,
+    cur_range: Option<(Id, Id)>,
+    next_len: u32,
+    cursors: Vec<PeriodCursor>,
+    cursors_len: u32
+}
+
+impl InvalidIds<'_> {
+    fn prepare_next_len(&mut self) -> bool {
+        // Sets up one cursor per minimal period for the next digit count, moving on to the next range if needed. Returns
+        // false when all ranges are exhausted.
+        loop {
+            let (start, end) = match self.cur_range {
+                Some(r) if self.next_len <= digit_count(r.1, self.base) => r,
+                _ => {
+                    let Some(r) = self.ranges.next().copied() else { return false };
+                    self.cur_range = Some(r);
+                    self.next_len = digit_count(r.0, self.base);
+                    continue;
+                }
+            };
+
+            let len = self.next_len;
+            self.next_len += 1;
+
+            self.cursors = divisors(len)
+                .into_iter()
+                .filter_map(|d| {
+                    let reported_period = self.rule.reported_period(len, d)?;
+                    let (first_unit, last_unit) = unit_range(start, end, len, d, self.base)?;
+                    Some(PeriodCursor {
+                        minimal_period: d,
+                        reported_period,
+                        multiplier: repetition_multiplier(len, d, self.base),
+                        next_unit: first_unit,
+                        last_unit
+                    })
+                })
+                .collect();
+            self.cursors_len = len;
+
+            return true;
+        }
+    }
+}
+
+impl Iterator for InvalidIds<'_> {
+    type Item = InvalidId;
+
+    fn next(&mut self) -> Option<InvalidId> {
+        loop {
+            // Skip units whose repetition has an even smaller period (these are reported by the cursor for that period)
+            let len = self.cursors_len;
+            let base = self.base;
+            for c in self.cursors.iter_mut() {
+                while c.next_unit <= c.last_unit
+                    && divisors(c.minimal_period).iter().any(|e| *e < c.minimal_period && has_period(c.next_unit * c.multiplier, len, *e, base)) {
+                    c.next_unit += 1;
+                }
+            }
+
+            // All cursors generate ascending numbers, so we merge them by always taking the smallest next number
+            let best = self.cursors
+                .iter_mut()
+                .filter(|c| c.next_unit <= c.last_unit)
+                .min_by_key(|c| c.next_unit * c.multiplier);
+
+            let Some(c) = best else {
+                if !self.prepare_next_len() {
+                    return None;
+                }
+                continue;
+            };
+
+            let id = c.next_unit * c.multiplier;
+            c.next_unit += 1;
+
+            let repetitions = len / c.reported_period;
+            return Some(InvalidId {
+                id,
+                range: self.cur_range.unwrap(),
+                unit: id / (base as Id).pow(len - c.reported_period),
+                repetitions
+            });
+        }
+    }
+}
+
+pub fn invalid_ids<'a>(input: &'a [(Id, Id)], rule: &RepetitionRule, base: u32) -> InvalidIds<'a> {
+    InvalidIds {
+        ranges: input.iter(),
+        rule: *rule,
+        base,
+        cur_range: None,
+        next_len: 0,
+        cursors: vec!(),
+        cursors_len: 0
+    }
+}
+
+pub fn write_invalid_ids_csv<W: Write>(input: &[(Id, Id)], rule: &RepetitionRule, base: u32, mut writer: W) -> Result<()> {
+    // All numbers are written in the given base
+    writeln!(writer, "id,range_start,range_end,unit,repetitions")?;
+    for i in invalid_ids(input, rule, base) {
+        let f = |n| format_in_base(n, base);
+        writeln!(writer, "{},{},{},{},{}", f(i.id), f(i.range.0), f(i.range.1), f(i.unit), i.repetitions)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{process_input, read_input_file};
+
+    #[test]
+    fn example_ids_match_sum() {
+        let input = read_input_file("../inputs/day2_example.txt").unwrap();
+        for rule in [RepetitionRule::first_star(), RepetitionRule::second_star(), RepetitionRule::exactly(2).with_primitive_unit()] {
+            let ids: Vec<_> = invalid_ids(&input, &rule, 10).collect();
+            assert_eq!(ids.iter().map(|i| i.id).sum::<Id>(), process_input(&input, &rule).unwrap());
+            assert!(ids.iter().all(|i| i.id >= i.range.0 && i.id <= i.range.1));
+            assert!(ids.iter().all(|i| i.unit.to_string().repeat(i.repetitions as usize) == i.id.to_string()));
+        }
+
+        let first_star: Vec<_> = invalid_ids(&input[1..2], &RepetitionRule::first_star(), 10).collect();
+        assert_eq!(first_star, vec![InvalidId { id: 99, range: (95, 115), unit: 9, repetitions: 2 }]);
+        let second_star: Vec<_> = invalid_ids(&input[1..2], &RepetitionRule::second_star(), 10).collect();
+        assert_eq!(second_star, vec![
+            InvalidId { id: 99, range: (95, 115), unit: 9, repetitions: 2 },
+            InvalidId { id: 111, range: (95, 115), unit: 1, repetitions: 3 }
+        ]);
+    }
+
+    #[test]
+    fn ascending_and_unit_choice() {
+        let ids: Vec<_> = invalid_ids(&[(1, 99999999)], &RepetitionRule::second_star(), 10).collect();
+        assert!(ids.windows(2).all(|w| w[0].id < w[1].id));
+        assert!(ids.contains(&InvalidId { id: 11111111, range: (1, 99999999), unit: 1, repetitions: 8 }));
+        assert!(ids.contains(&InvalidId { id: 12121212, range: (1, 99999999), unit: 12, repetitions: 4 }));
+
+        // First star only accepts two halves, even if there is a shorter repeating unit
+        let ids: Vec<_> = invalid_ids(&[(1, 99999999)], &RepetitionRule::first_star(), 10).collect();
+        assert!(ids.contains(&InvalidId { id: 11111111, range: (1, 99999999), unit: 1111, repetitions: 2 }));
+        assert!(!ids.iter().any(|i| i.id == 111));
+    }
+
+    #[test]
+    fn csv_output() {
+        let mut out = vec!();
+        write_invalid_ids_csv(&[(95, 115)], &RepetitionRule::second_star(), 10, &mut out).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "id,range_start,range_end,unit,repetitions\n99,95,115,9,2\n111,95,115,1,3\n");
+
+        let mut out = vec!();
+        write_invalid_ids_csv(&[(0b100, 0b111111)], &RepetitionRule::second_star(), 2, &mut out).unwrap();
+        let csv = String::from_utf8(out).unwrap();
+        assert!(csv.contains("\n111,100,111111,1,3\n1010,100,111111,10,2\n"));
+    }
+}
@@ -0,0 +1,194 @@
+// Joltage selection with wiring constraints. With forbidden/required positions or a minimum gap between the chosen
+// batteries, the greedy/stack approach no longer works, so we solve these exactly with dynamic programming.
+
+use anyhow::{bail, Result};
+
+use crate::{select_with_stack, Selection};
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Objective {
+    Maximize,
+    Minimize
+}
+
+impl Objective {
+    pub fn is_better<T: Ord>(self, a: T, b: T) -> bool {
+        match self {
+            Objective::Maximize => a > b,
+            Objective::Minimize => a < b
+        }
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Constraints {
+    pub objective: Objective,
+    pub min_gap: usize,  // minimum distance between chosen positions (1 allows neighboring batteries)
+    pub forbidden: Vec<usize>,
+    pub required: Vec<usize>
+}
+
+impl Default for Constraints {
+    fn default() -> Self {
+        Constraints { objective: Objective::Maximize, min_gap: 1, forbidden: vec!(), required: vec!() }
+    }
+}
+
+pub fn calculate_joltage_constrained(batteries: &[u8], digit_count: usize, constraints: &Constraints) -> Result<Selection> {
+    let len = batteries.len();
+    if constraints.min_gap == 0 {
+        bail!("Minimum gap must be at least 1!");
+    }
+    if let Some(pos) = constraints.forbidden.iter().chain(constraints.required.iter()).find(|p| **p >= len) {
+        bail!("Position {} is outside of the bank (length {})!", pos, len);
+    }
+
+    // Without any wiring constraints, the stack approach is exact
+    if constraints.min_gap == 1 && constraints.forbidden.is_empty() && constraints.required.is_empty() {
+        return select_with_stack(batteries, digit_count, constraints.objective);
+    }
+
+    let mut forbidden = vec![false; len];
+    constraints.forbidden.iter().for_each(|p| forbidden[*p] = true);
+    let mut required = vec![false; len];
+    constraints.required.iter().for_each(|p| required[*p] = true);
+    if let Some(pos) = (0..len).find(|p| forbidden[*p] && required[*p]) {
+        bail!("Position {} is both forbidden and required!", pos);
+    }
+
+    // next_required[i] is the first required position >= i (or len if there is none)
+    let mut next_required = vec![len; len + 1];
+    for i in (0..len).rev() {
+        next_required[i] = if required[i] { i } else { next_required[i + 1] };
+    }
+
+    // The optimal choice of j batteries from positions >= i either takes position i (followed by the optimal choice of
+    // j - 1 batteries after the gap) or skips it. We only store that decision per state and rebuild the indices at the
+    // end. To compare two candidates without materializing them, every state of the previous layer (j - 1 batteries) has
+    // a rank among all choices of that length, so a choice of j batteries is described by its first digit and the rank of
+    // the rest. All candidates for the same j have the same length, so this order is the same as the order of their
+    // values. On ties, taking position i wins since it results in earlier indices. This takes O(n * k * log n) time and
+    // O(n * k) memory for the decisions.
+    let mut takes: Vec<Vec<Option<bool>>> = vec![vec![None; len + 1]; digit_count + 1];  // indexed by [j][i], None if impossible
+    let mut prev_ranks: Vec<Option<usize>> = (0..=len).map(|i| (next_required[i] == len).then_some(0)).collect();
+
+    for layer_takes in takes.iter_mut().skip(1) {
+        let mut keys: Vec<Option<(u8, usize)>> = vec![None; len + 1];  // first digit and rank of the rest per state
+
+        for i in (0..len).rev() {
+            let after_gap = (i + constraints.min_gap).min(len);
+            let skip = if required[i] { None } else { keys[i + 1] };
+            let take = if !forbidden[i] && next_required[i + 1] >= after_gap { prev_ranks[after_gap].map(|r| (batteries[i], r)) } else { None };
+
+            (keys[i], layer_takes[i]) = match (take, skip) {
+                (Some(t), Some(s)) if constraints.objective.is_better(s, t) => (Some(s), Some(false)),
+                (Some(t), _) => (Some(t), Some(true)),
+                (None, Some(s)) => (Some(s), Some(false)),
+                (None, None) => (None, None)
+            };
+        }
+
+        let mut sorted_keys: Vec<(u8, usize)> = keys.iter().flatten().copied().collect();
+        sorted_keys.sort_unstable();
+        sorted_keys.dedup();
+        prev_ranks = keys.iter().map(|k| k.map(|k| sorted_keys.binary_search(&k).unwrap())).collect();
+    }
+
+    if prev_ranks[0].is_none() {
+        bail!("No selection of {} batteries satisfies the constraints!", digit_count);
+    }
+
+    let mut indices = Vec::with_capacity(digit_count);
+    let (mut i, mut j) = (0, digit_count);
+    while j > 0 {
+        if takes[j][i] == Some(true) {
+            indices.push(i);
+            i = (i + constraints.min_gap).min(len);
+            j -= 1;
+        } else {
+            i += 1;
+        }
+    }
+
+    Ok(Selection::from_indices(batteries, indices))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::read_input_file;
+
+    fn brute_force(batteries: &[u8], digit_count: usize, constraints: &Constraints) -> Option<u64> {
+        // Tries all subsets of positions
+        let values = (0u32..(1 << batteries.len()))
+            .filter(|mask| mask.count_ones() as usize == digit_count)
+            .map(|mask| (0..batteries.len()).filter(|i| mask & (1 << i) != 0).collect::<Vec<_>>())
+            .filter(|indices| {
+                indices.windows(2).all(|w| w[1] - w[0] >= constraints.min_gap)
+                    && !indices.iter().any(|i| constraints.forbidden.contains(i))
+                    && constraints.required.iter().all(|r| indices.contains(r))
+            })
+            .map(|indices| indices.iter().fold(0u64, |acc, i| acc * 10 + batteries[*i] as u64));
+
+        match constraints.objective {
+            Objective::Maximize => values.max(),
+            Objective::Minimize => values.min()
+        }
+    }
+
+    #[test]
+    fn matches_brute_force() {
+        let input = read_input_file("../inputs/day3_example.txt").unwrap();
+        let banks: Vec<Vec<u8>> = input.iter().map(|b| b[..12].to_vec()).chain([vec![3, 1, 4, 1, 5, 9, 2, 6, 5, 3, 5, 8]]).collect();
+
+        let constraint_sets = [
+            Constraints::default(),
+            Constraints { objective: Objective::Minimize, ..Constraints::default() },
+            Constraints { min_gap: 2, ..Constraints::default() },
+            Constraints { objective: Objective::Minimize, min_gap: 3, ..Constraints::default() },
+            Constraints { forbidden: vec![0, 5, 6], ..Constraints::default() },
+            Constraints { required: vec![3, 10], ..Constraints::default() },
+            Constraints { objective: Objective::Minimize, min_gap: 2, forbidden: vec![1], required: vec![4] }
+        ];
+
+        for bank in &banks {
+            for constraints in &constraint_sets {
+                for digit_count in 0..=6 {
+                    let expected = brute_force(bank, digit_count, constraints);
+                    let res = calculate_joltage_constrained(bank, digit_count, constraints);
+                    assert_eq!(res.as_ref().ok().map(|s| s.value(10).try_into().unwrap()), expected, "{:?} {:?} {}", bank, constraints, digit_count);
+
+                    if let Ok(selection) = res {
+                        assert!(selection.indices.windows(2).all(|w| w[1] - w[0] >= constraints.min_gap));
+                        assert!(constraints.required.iter().all(|r| selection.indices.contains(r)));
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn long_banks() {
+        // With a zero after every battery and a minimum gap of 2, the best choice takes the original batteries, so the
+        // result has to match the stack approach on the original bank
+        let input = read_input_file("../inputs/day3_example.txt").unwrap();
+        let bank: Vec<u8> = input.concat().repeat(25);
+        let padded: Vec<u8> = bank.iter().flat_map(|d| [*d, 0]).collect();
+        let constraints = Constraints { min_gap: 2, ..Constraints::default() };
+
+        let selection = calculate_joltage_constrained(&padded, 300, &constraints).unwrap();
+        assert_eq!(selection.value(10), select_with_stack(&bank, 300, Objective::Maximize).unwrap().value(10));
+        assert!(selection.indices.iter().all(|i| i % 2 == 0));
+    }
+
+    #[test]
+    fn invalid_constraints() {
+        let bank = vec![1, 2, 3, 4];
+        let required_and_forbidden = Constraints { forbidden: vec![1], required: vec![1], ..Constraints::default() };
+        assert!(calculate_joltage_constrained(&bank, 2, &required_and_forbidden).is_err());
+        let out_of_bounds = Constraints { required: vec![4], ..Constraints::default() };
+        assert!(calculate_joltage_constrained(&bank, 2, &out_of_bounds).is_err());
+        let too_wide = Constraints { min_gap: 4, ..Constraints::default() };
+        assert!(calculate_joltage_constrained(&bank, 2, &too_wide).is_err());
+    }
+}
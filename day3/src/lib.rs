@@ -0,0 +1,244 @@
+use anyhow::{bail, Context, Result};
+use num_bigint::BigUint;
+use std::fs::read_to_string;
+use std::path::Path;
+
+pub mod constrained;
+mod parser;
+pub mod ranking;
+
+use constrained::Objective;
+use parser::parse_banks;
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Selection {
+    indices: Vec<usize>,  // positions of the chosen batteries within the bank (ascending)
+    digits: Vec<u8>
+}
+
+impl Selection {
+    fn from_indices(batteries: &[u8], indices: Vec<usize>) -> Self {
+        let digits = indices.iter().map(|idx| batteries[*idx]).collect();
+        Selection { indices, digits }
+    }
+
+    fn value(&self, radix: u32) -> BigUint {
+        // Arbitrary precision since we can select as many batteries as the bank has
+        self.digits.iter().fold(BigUint::ZERO, |acc, d| acc * radix + *d as u32)
+    }
+
+    pub fn highlight(&self, batteries: &[u8]) -> String {
+        // Renders the bank with the chosen batteries in brackets, e.g., "[9][8]7654321111111" (ratings above 9 are shown
+        // as letters like in the input)
+        let mut chosen = self.indices.iter().peekable();
+        batteries
+            .iter()
+            .enumerate()
+            .map(|(idx, d)| {
+                let c = char::from_digit(*d as u32, 36).unwrap().to_ascii_uppercase();
+                if chosen.next_if_eq(&&idx).is_some() { format!("[{}]", c) } else { c.to_string() }
+            })
+            .collect()
+    }
+}
+
+fn calculate_joltage_two_digits(batteries: &[u8]) -> Result<Selection> {
+    if batteries.len() < 2 {
+        bail!("Cannot select 2 batteries from a bank with only {}!", batteries.len());
+    }
+
+    let first_max_index = batteries[0..(batteries.len()-1)].iter().enumerate().rev().max_by_key(|&(_idx, &val)| val).unwrap().0;
+    let second_max_index = batteries.iter().enumerate().skip(first_max_index+1).rev().max_by_key(|&(_idx, &val)| val).unwrap().0;
+    Ok(Selection::from_indices(batteries, vec![first_max_index, second_max_index]))
+}
+
+pub fn calculate_joltage_multi_digit_greedy(mut batteries: &[u8], mut remaining_digits: usize) -> u64 {
+    // Greedily choose maximum in available slice that always makes sure that there are enough digits left
+    // (this is the original O(n*k) approach, kept as a reference implementation)
+    let mut res = String::new();
+
+    while remaining_digits > 0 {
+        remaining_digits -= 1;
+        let last_choosable_index = batteries.len() - remaining_digits;  // e.g., len is 10, 3 digits need to remain -> we can choose max index 7
+        let first_max_index = batteries[0..last_choosable_index].iter().enumerate().rev().max_by_key(|&(_idx, &val)| val).unwrap().0;
+        res += &batteries[first_max_index].to_string();
+        batteries = &batteries[(first_max_index+1)..];
+    }
+
+    res.parse().unwrap()
+}
+
+fn calculate_joltage_multi_digit(batteries: &[u8], digit_count: usize) -> Result<Selection> {
+    select_with_stack(batteries, digit_count, Objective::Maximize)
+}
+
+fn select_with_stack(batteries: &[u8], digit_count: usize, objective: Objective) -> Result<Selection> {
+    // Monotonic stack in O(n): We are allowed to drop len - digit_count batteries. Whenever the current battery is larger
+    // (smaller when minimizing) than the top of the stack, dropping the top improves the result (as long as we can still
+    // drop). Equal values are kept so that the earliest occurrence is chosen, like the greedy approach does.
+    if digit_count > batteries.len() {
+        bail!("Cannot select {} batteries from a bank with only {}!", digit_count, batteries.len());
+    }
+
+    let mut droppable = batteries.len() - digit_count;
+    let mut stack: Vec<usize> = Vec::with_capacity(batteries.len());
+
+    for (idx, val) in batteries.iter().enumerate() {
+        while droppable > 0 && stack.last().is_some_and(|top| objective.is_better(*val, batteries[*top])) {
+            stack.pop();
+            droppable -= 1;
+        }
+        stack.push(idx);
+    }
+    stack.truncate(digit_count);
+
+    Ok(Selection::from_indices(batteries, stack))
+}
+
+pub fn calculate_total_joltage_two_digits(input: &[Vec<u8>], radix: u32) -> Result<BigUint> {
+    // The selections themselves do not depend on the radix, only the resulting joltage values do
+    input
+        .iter()
+        .enumerate()
+        .map(|(idx, b)| Ok(calculate_joltage_two_digits(b).with_context(|| format!("Bank {}", idx + 1))?.value(radix)))
+        .sum()
+}
+
+pub fn calculate_total_joltage_multi_digit(input: &[Vec<u8>], digit_count: usize, radix: u32) -> Result<BigUint> {
+    input
+        .iter()
+        .enumerate()
+        .map(|(idx, b)| Ok(calculate_joltage_multi_digit(b, digit_count).with_context(|| format!("Bank {}", idx + 1))?.value(radix)))
+        .sum()
+}
+
+pub fn explain_selections(input: &[Vec<u8>], digit_count: usize, radix: u32) -> Result<String> {
+    // One line per bank with the chosen batteries highlighted and the resulting joltage
+    input
+        .iter()
+        .enumerate()
+        .map(|(idx, b)| {
+            let selection = calculate_joltage_multi_digit(b, digit_count)?;
+            // The joltage is written digit by digit in the radix of the input as well (keeping leading zeros)
+            let Some(joltage) = selection.digits.iter().map(|d| char::from_digit(*d as u32, radix)).collect::<Option<String>>() else {
+                bail!("Bank {}: Rating does not fit into radix {}!", idx + 1, radix)
+            };
+            Ok(format!("{} -> {}\n", selection.highlight(b), joltage.to_uppercase()))
+        })
+        .collect()
+}
+
+pub fn read_input_file<P: AsRef<Path>>(input_path: P) -> Result<Vec<Vec<u8>>> {
+    read_input_file_in_radix(input_path, 10)
+}
+
+fn read_input_file_in_radix<P: AsRef<Path>>(input_path: P, radix: u32) -> Result<Vec<Vec<u8>>> {
+    // Both stars need at least two batteries per bank
+    let input = read_to_string(input_path)?;
+    Ok(parse_banks(&input, radix, 2)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn random_banks(count: usize, max_len: usize) -> Vec<Vec<u8>> {
+        // Simple linear congruential generator, good enough to produce test banks with many repeated digits
+        let mut state: u64 = 0x2025;
+        let mut next = move |m: u64| {
+            state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+            (state >> 33) % m
+        };
+
+        (0..count)
+            .map(|_| {
+                let len = 1 + next(max_len as u64) as usize;
+                let digit_range = 1 + next(9);  // small ranges lead to many ties
+                (0..len).map(|_| 1 + next(digit_range) as u8).collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn example_first_star() {
+        let input = read_input_file("../inputs/day3_example.txt").unwrap();
+        assert_eq!(calculate_total_joltage_two_digits(&input, 10).unwrap(), BigUint::from(357u32));
+    }
+
+    #[test]
+    fn example_second_star() {
+        let input = read_input_file("../inputs/day3_example.txt").unwrap();
+        assert_eq!(calculate_total_joltage_multi_digit(&input, 12, 10).unwrap(), BigUint::from(3121910778619u64));
+    }
+
+    #[test]
+    fn stack_matches_greedy() {
+        for bank in random_banks(500, 30) {
+            for digit_count in 1..=bank.len().min(19) {
+                let selection = calculate_joltage_multi_digit(&bank, digit_count).unwrap();
+                assert_eq!(selection.value(10), BigUint::from(calculate_joltage_multi_digit_greedy(&bank, digit_count)), "{:?} {}", bank, digit_count);
+                assert_eq!(selection.indices.len(), digit_count);
+                assert!(selection.indices.windows(2).all(|w| w[0] < w[1]));
+            }
+        }
+
+        let input = read_input_file("../inputs/day3_example.txt").unwrap();
+        assert_eq!(calculate_joltage_multi_digit(&input[1], 2).unwrap(), Selection { indices: vec![0, 14], digits: vec![8, 9] });
+    }
+
+    #[test]
+    fn selections_and_highlighting() {
+        let input = read_input_file("../inputs/day3_example.txt").unwrap();
+        for bank in &input {
+            assert_eq!(calculate_joltage_two_digits(bank).unwrap(), calculate_joltage_multi_digit(bank, 2).unwrap());
+        }
+
+        let selection = calculate_joltage_two_digits(&input[3]).unwrap();
+        assert_eq!(selection, Selection { indices: vec![6, 11], digits: vec![9, 2] });
+        assert_eq!(selection.highlight(&input[3]), "818181[9]1111[2]111");
+
+        let explanation = explain_selections(&input, 12, 10).unwrap();
+        assert_eq!(explanation.lines().next().unwrap(), "[9][8][7][6][5][4][3][2][1][1][1][1]111 -> 987654321111");
+    }
+
+    #[test]
+    fn arbitrary_digit_counts() {
+        let input = read_input_file("../inputs/day3_example.txt").unwrap();
+
+        // Selecting all batteries yields the whole bank as number
+        let all: BigUint = input.iter().map(|b| b.iter().fold(BigUint::ZERO, |acc, d| acc * 10u32 + *d as u32)).sum();
+        assert_eq!(calculate_total_joltage_multi_digit(&input, 15, 10).unwrap(), all);
+        assert_eq!(calculate_joltage_multi_digit(&input[0], 0).unwrap().value(10), BigUint::ZERO);
+
+        let long_bank: Vec<u8> = (0..100).map(|i| (i % 9 + 1) as u8).collect();
+        let selection = calculate_joltage_multi_digit(&long_bank, 40).unwrap();
+        assert_eq!(selection.value(10).to_string(), "9999999567891234567891234567891234567891");
+
+        let err = calculate_total_joltage_multi_digit(&input, 16, 10).unwrap_err();
+        assert_eq!(format!("{:#}", err), "Bank 1: Cannot select 16 batteries from a bank with only 15!");
+        assert!(calculate_joltage_two_digits(&[7]).is_err());
+    }
+
+    #[test]
+    fn hexadecimal_banks() {
+        let input = read_input_file_in_radix("../inputs/day3_example_hex.txt", 16).unwrap();
+        assert_eq!(input[0], vec![1, 15, 3, 10, 2, 11, 4]);
+
+        let selection = calculate_joltage_multi_digit(&input[0], 3).unwrap();
+        assert_eq!(selection.highlight(&input[0]), "1[F]3A2[B][4]");
+        assert_eq!(selection.value(16), BigUint::from(0xFB4u32));
+        assert_eq!(calculate_total_joltage_multi_digit(&input, 2, 16).unwrap(), BigUint::from(0xFB + 0xED + 0xFFu32));
+        assert_eq!(calculate_total_joltage_two_digits(&input, 16).unwrap(), BigUint::from(0xFB + 0xED + 0xFFu32));
+        assert_eq!(explain_selections(&input, 3, 16).unwrap(), "1[F]3A2[B][4] -> FB4\n9[E]0[D][8] -> ED8\nC0[F][F][C] -> FFC\n");
+        assert_eq!(explain_selections(&[vec![0, 5]], 2, 10).unwrap(), "[0][5] -> 05\n");
+        assert!(explain_selections(&input, 3, 10).is_err());
+
+        // Decimal input is identical in both radixes, but the joltage values are interpreted differently
+        let decimal = read_input_file_in_radix("../inputs/day3_example.txt", 16).unwrap();
+        assert_eq!(decimal, read_input_file("../inputs/day3_example.txt").unwrap());
+        assert_eq!(calculate_total_joltage_two_digits(&decimal, 16).unwrap(), BigUint::from(0x98 + 0x89 + 0x78 + 0x92u32));
+
+        let err = read_input_file("../inputs/day3_example_hex.txt").unwrap_err();
+        assert_eq!(err.to_string(), "Line 1, column 2: 'F' is not a valid rating in base 10!");
+    }
+}
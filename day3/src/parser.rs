@@ -0,0 +1,75 @@
+// Validating parser for battery banks. Instead of panicking on the first unexpected character, it reports where exactly
+// the input is broken. Ratings can be given in any radix up to 36 (e.g., 16 for ratings 0-F).
+
+use std::fmt;
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum BankError {
+    InvalidRating { line: usize, column: usize, found: char, radix: u32 },
+    EmptyBank { line: usize },
+    TooShort { line: usize, len: usize, min_len: usize }
+}
+
+impl fmt::Display for BankError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BankError::InvalidRating { line, column, found, radix } =>
+                write!(f, "Line {}, column {}: '{}' is not a valid rating in base {}!", line, column, found, radix),
+            BankError::EmptyBank { line } =>
+                write!(f, "Line {}: Bank is empty!", line),
+            BankError::TooShort { line, len, min_len } =>
+                write!(f, "Line {}: Bank has only {} batteries, at least {} are required!", line, len, min_len)
+        }
+    }
+}
+
+impl std::error::Error for BankError {}
+
+pub fn parse_banks(input: &str, radix: u32, min_len: usize) -> Result<Vec<Vec<u8>>, BankError> {
+    // Line and column numbers are 1-based, trailing whitespace (e.g., "\r") is ignored
+    assert!((2..=36).contains(&radix), "Radix must be between 2 and 36!");
+
+    input
+        .lines()
+        .enumerate()
+        .map(|(idx, l)| {
+            let line = idx + 1;
+            let bank = l
+                .trim_end()
+                .chars()
+                .enumerate()
+                .map(|(col, c)| {
+                    c.to_digit(radix).map(|d| d as u8).ok_or(BankError::InvalidRating { line, column: col + 1, found: c, radix })
+                })
+                .collect::<Result<Vec<u8>, _>>()?;
+
+            if bank.is_empty() {
+                Err(BankError::EmptyBank { line })
+            } else if bank.len() < min_len {
+                Err(BankError::TooShort { line, len: bank.len(), min_len })
+            } else {
+                Ok(bank)
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn errors_with_position() {
+        assert_eq!(parse_banks("12345\n987x1\n", 10, 2), Err(BankError::InvalidRating { line: 2, column: 4, found: 'x', radix: 10 }));
+        assert_eq!(parse_banks("12345\n\n12\n", 10, 2), Err(BankError::EmptyBank { line: 2 }));
+        assert_eq!(parse_banks("12345\n1\n", 10, 2), Err(BankError::TooShort { line: 2, len: 1, min_len: 2 }));
+        assert_eq!(parse_banks("12A\n", 10, 2).unwrap_err().to_string(), "Line 1, column 3: 'A' is not a valid rating in base 10!");
+        assert_eq!(parse_banks("12345\r\n67\r\n", 10, 2), Ok(vec![vec![1, 2, 3, 4, 5], vec![6, 7]]));
+    }
+
+    #[test]
+    fn hexadecimal_ratings() {
+        assert_eq!(parse_banks("0fA9\nF1\n", 16, 2), Ok(vec![vec![0, 15, 10, 9], vec![15, 1]]));
+        assert_eq!(parse_banks("0fG9\n", 16, 2), Err(BankError::InvalidRating { line: 1, column: 3, found: 'G', radix: 16 }));
+    }
+}
@@ -0,0 +1,172 @@
+// Not only the best joltage of a bank but all distinct joltages in descending order. Since all joltages for a digit count
+// have the same length, descending values are the same as lexicographically descending digit strings, so a depth-first
+// search over the digits (largest first) produces them in order. Each distinct string is only reached once by always
+// using the earliest occurrence of the next digit.
+
+use num_bigint::BigUint;
+
+use crate::Selection;
+
+fn next_occurrences(batteries: &[u8], alphabet: &[u8]) -> Vec<Vec<usize>> {
+    // next[i][a] is the first position >= i with rating alphabet[a] (or len if there is none)
+    let len = batteries.len();
+    let mut next = vec![vec![len; alphabet.len()]; len + 1];
+    for i in (0..len).rev() {
+        next[i] = next[i + 1].clone();
+        next[i][alphabet.binary_search(&batteries[i]).unwrap()] = i;
+    }
+    next
+}
+
+fn alphabet(batteries: &[u8]) -> Vec<u8> {
+    let mut res = batteries.to_vec();
+    res.sort_unstable();
+    res.dedup();
+    res
+}
+
+pub struct Joltages<'a> {
+    batteries: &'a [u8],
+    digit_count: usize,
+    next: Vec<Vec<usize>>,
+    stack: Vec<Vec<usize>>  // chosen indices of the prefixes still to be explored (the top is the largest one)
+}
+
+impl Iterator for Joltages<'_> {
+    type Item = Selection;
+
+    fn next(&mut self) -> Option<Selection> {
+        while let Some(indices) = self.stack.pop() {
+            let remaining = self.digit_count - indices.len();
+            if remaining == 0 {
+                return Some(Selection::from_indices(self.batteries, indices));
+            }
+
+            // Push the children with ascending digits so that the largest one is explored first. Only children that still
+            // leave enough batteries for the remaining digits are pushed, so every explored prefix yields a joltage.
+            let start = indices.last().map_or(0, |i| i + 1);
+            for pos in self.next[start].iter().copied() {
+                if pos + remaining <= self.batteries.len() {
+                    self.stack.push([indices.as_slice(), &[pos]].concat());
+                }
+            }
+        }
+
+        None
+    }
+}
+
+pub fn joltages_descending(batteries: &[u8], digit_count: usize) -> Joltages<'_> {
+    let next = next_occurrences(batteries, &alphabet(batteries));
+    let stack = if digit_count <= batteries.len() { vec![vec!()] } else { vec!() };
+    Joltages { batteries, digit_count, next, stack }
+}
+
+fn distinct_counts(batteries: &[u8], digit_count: usize, next: &[Vec<usize>]) -> Vec<Vec<BigUint>> {
+    // counts[i][j] is the number of distinct joltages with j digits that can be formed from the batteries at positions >= i
+    let len = batteries.len();
+    let mut counts = vec![vec![BigUint::ZERO; digit_count + 1]; len + 1];
+    for i in (0..=len).rev() {
+        counts[i][0] = BigUint::from(1u32);
+        for j in 1..=digit_count {
+            counts[i][j] = next[i].iter().filter(|p| **p < len).map(|p| &counts[p + 1][j - 1]).sum();
+        }
+    }
+    counts
+}
+
+pub fn count_distinct_joltages(batteries: &[u8], digit_count: usize) -> BigUint {
+    let next = next_occurrences(batteries, &alphabet(batteries));
+    distinct_counts(batteries, digit_count, &next).swap_remove(0).swap_remove(digit_count)
+}
+
+pub fn nth_best_joltage(batteries: &[u8], digit_count: usize, rank: usize) -> Option<Selection> {
+    // Returns the joltage with the given (0-based) rank among the distinct joltages without enumerating the better ones:
+    // The counts tell us how many joltages start with each digit, so we can skip whole subtrees.
+    let alphabet = alphabet(batteries);
+    let next = next_occurrences(batteries, &alphabet);
+    let counts = distinct_counts(batteries, digit_count, &next);
+    let mut rank = BigUint::from(rank);
+    if digit_count > batteries.len() || rank >= counts[0][digit_count] {
+        return None;
+    }
+
+    let mut indices = vec!();
+    let mut start = 0;
+    for remaining in (1..=digit_count).rev() {
+        for a in (0..alphabet.len()).rev() {
+            let pos = next[start][a];
+            if pos == batteries.len() {
+                continue;
+            }
+
+            let subtree = &counts[pos + 1][remaining - 1];
+            if rank < *subtree {
+                indices.push(pos);
+                start = pos + 1;
+                break;
+            }
+            rank -= subtree;
+        }
+    }
+
+    Some(Selection::from_indices(batteries, indices))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{calculate_joltage_multi_digit, read_input_file};
+
+    fn brute_force(batteries: &[u8], digit_count: usize) -> Vec<u64> {
+        // All distinct joltages in descending order
+        let mut values: Vec<u64> = (0u32..(1 << batteries.len()))
+            .filter(|mask| mask.count_ones() as usize == digit_count)
+            .map(|mask| (0..batteries.len()).filter(|i| mask & (1 << i) != 0).fold(0, |acc, i| acc * 10 + batteries[i] as u64))
+            .collect();
+        values.sort_unstable_by(|a, b| b.cmp(a));
+        values.dedup();
+        values
+    }
+
+    #[test]
+    fn matches_brute_force() {
+        let input = read_input_file("../inputs/day3_example.txt").unwrap();
+        let banks: Vec<Vec<u8>> = input.into_iter().chain([vec![3, 1, 4, 1, 5, 9, 2, 6, 5, 3, 5, 8], vec![0, 0, 1, 0]]).collect();
+
+        for bank in &banks {
+            for digit_count in 0..=6 {
+                let expected = brute_force(bank, digit_count);
+                let values: Vec<u64> = joltages_descending(bank, digit_count).map(|s| s.value(10).try_into().unwrap()).collect();
+                assert_eq!(values, expected, "{:?} {}", bank, digit_count);
+                assert_eq!(count_distinct_joltages(bank, digit_count), BigUint::from(expected.len()));
+
+                for (rank, value) in expected.iter().enumerate() {
+                    assert_eq!(nth_best_joltage(bank, digit_count, rank).unwrap().value(10), BigUint::from(*value));
+                }
+                assert!(nth_best_joltage(bank, digit_count, expected.len()).is_none());
+            }
+        }
+    }
+
+    #[test]
+    fn lazy_top_joltages() {
+        let input = read_input_file("../inputs/day3_example.txt").unwrap();
+        for bank in &input {
+            assert_eq!(joltages_descending(bank, 12).next(), Some(calculate_joltage_multi_digit(bank, 12).unwrap()));
+        }
+
+        let top: Vec<String> = joltages_descending(&input[0], 12).take(3).map(|s| s.value(10).to_string()).collect();
+        assert_eq!(top, vec!["987654321111", "987654311111", "987654211111"]);
+
+        // A long bank has far too many subsequences to enumerate them, but the top ones are still cheap to get
+        let long_bank: Vec<u8> = (0..200).map(|i| (i * 7 % 9 + 1) as u8).collect();
+        assert!(count_distinct_joltages(&long_bank, 40) > BigUint::from(u64::MAX));
+        let top: Vec<BigUint> = joltages_descending(&long_bank, 40).take(1000).map(|s| s.value(10)).collect();
+        assert!(top.windows(2).all(|w| w[0] > w[1]));
+        assert_eq!(nth_best_joltage(&long_bank, 40, 999).unwrap().value(10), top[999]);
+
+        assert_eq!(joltages_descending(&[1, 2], 3).next(), None);
+        assert_eq!(count_distinct_joltages(&[1, 2], 3), BigUint::ZERO);
+    }
+}
@@ -0,0 +1,326 @@
+// Bit-packed field for huge floor plans (one bit per cell instead of one byte). Neighbor counts are computed for 64 cells
+// at once: the eight neighbor bits of each cell come from shifting the row above, the row itself and the row below, and
+// are summed up with bit-sliced adders (one word per bit of the count). Only the puzzle neighborhood (8 surrounding cells,
+// clamped at the edges) with a "fewer than n neighbors" rule is supported.
+// A BitGrid keeps the whole floor in memory, i.e., num_rows * ceil(num_cols / 64) * 8 bytes (about 1.1 MB for 3000x3000,
+// but about 125 GB for 10^6 x 10^6). For floors that large, remove_accessible_streaming reads the floor row by row and
+// only keeps a window of three rows (about 375 KB for a million columns). It does one synchronous round and writes the
+// updated floor, so the rounds of the second star can be done by feeding its output back in (e.g., via files on disk).
+
+use std::io::{BufRead, Error, ErrorKind, Result, Write};
+
+use crate::Field;
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BitGrid {
+    num_rows: usize,
+    num_cols: usize,
+    words_per_row: usize,
+    bits: Vec<u64>  // row-major, column c of a row is bit c % 64 of word c / 64 (unused bits of the last word are zero)
+}
+
+impl BitGrid {
+    pub fn new(num_rows: usize, num_cols: usize) -> Self {
+        let words_per_row = num_cols.div_ceil(64);
+        BitGrid { num_rows, num_cols, words_per_row, bits: vec![0; num_rows * words_per_row] }
+    }
+
+    pub fn from_field(field: &Field) -> Self {
+        let mut res = BitGrid::new(field.num_rows(), field.num_columns());
+        for (row, cells) in field.rows_iter().enumerate() {
+            for (col, roll) in cells.enumerate() {
+                res.set(row, col, *roll);
+            }
+        }
+        res
+    }
+
+    pub fn parse(input: &str) -> Self {
+        BitGrid::read(input.as_bytes()).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    pub fn read<R: BufRead>(input: R) -> Result<Self> {
+        // Same format as the dense input ('@' is a roll, everything else is empty), packed line by line without going
+        // through a Field (or keeping the whole input as text)
+        let mut res = BitGrid::new(0, 0);
+        for line in input.lines() {
+            let line = line?;
+            if res.num_rows == 0 {
+                res = BitGrid::new(0, line.len());
+            }
+            res.bits.resize((res.num_rows + 1) * res.words_per_row, 0);
+            let start = res.num_rows * res.words_per_row;
+            pack_row(&line, res.num_rows + 1, res.num_cols, &mut res.bits[start..])?;
+            res.num_rows += 1;
+        }
+        Ok(res)
+    }
+
+    pub fn to_field(&self) -> Field {
+        let rows: Vec<Vec<bool>> = (0..self.num_rows).map(|row| (0..self.num_cols).map(|col| self.get(row, col)).collect()).collect();
+        Field::from_rows(&rows).unwrap()
+    }
+
+    pub fn get(&self, row: usize, col: usize) -> bool {
+        self.bits[row * self.words_per_row + col / 64] & (1 << (col % 64)) != 0
+    }
+
+    pub fn set(&mut self, row: usize, col: usize, roll: bool) {
+        let word = &mut self.bits[row * self.words_per_row + col / 64];
+        if roll { *word |= 1 << (col % 64) } else { *word &= !(1 << (col % 64)) }
+    }
+
+    pub fn count_rolls(&self) -> usize {
+        self.bits.iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    fn row(&self, row: usize) -> &[u64] {
+        &self.bits[(row * self.words_per_row)..((row + 1) * self.words_per_row)]
+    }
+
+    pub fn remove_accessible(&mut self, threshold: usize, dirty: Option<&[bool]>) -> (usize, Vec<bool>) {
+        // Removes all rolls with fewer than threshold neighbors at once. Rows that are not marked as dirty are skipped
+        // (nothing can have changed there). Returns the number of removed rolls and the rows whose neighborhood changed.
+        let mut removed = 0;
+        let mut changed = vec![false; self.num_rows];
+        let empty = vec![0; self.words_per_row];
+        let mut prev_row = vec![0; self.words_per_row];  // original content of the row above (which is already updated)
+        let mut cur_row = vec![0; self.words_per_row];
+        let mut accessible = vec![0; self.words_per_row];
+
+        for row in 0..self.num_rows {
+            cur_row.copy_from_slice(self.row(row));
+            if dirty.is_none_or(|d| d[row]) {
+                let below = if row + 1 < self.num_rows { self.row(row + 1) } else { &empty };
+                accessible_in_row(&prev_row, &cur_row, below, threshold, &mut accessible);
+
+                let count: usize = accessible.iter().map(|w| w.count_ones() as usize).sum();
+                if count > 0 {
+                    removed += count;
+                    let start = row * self.words_per_row;
+                    for (w, a) in accessible.iter().enumerate() {
+                        self.bits[start + w] &= !a;
+                    }
+                    changed[row.saturating_sub(1)..(row + 2).min(self.num_rows)].fill(true);
+                }
+            }
+            std::mem::swap(&mut prev_row, &mut cur_row);
+        }
+
+        (removed, changed)
+    }
+
+    pub fn simulate_removal(&mut self, threshold: usize) -> Vec<usize> {
+        // Returns the number of removed rolls per round, only rows next to changed rows are re-examined
+        let mut rounds = vec!();
+        let mut dirty = None;
+        loop {
+            let (removed, changed) = self.remove_accessible(threshold, dirty.as_deref());
+            if removed == 0 {
+                return rounds;
+            }
+            rounds.push(removed);
+            dirty = Some(changed);
+        }
+    }
+}
+
+fn pack_row(line: &str, line_no: usize, num_cols: usize, row: &mut [u64]) -> Result<()> {
+    if line.len() != num_cols {
+        return Err(Error::new(ErrorKind::InvalidData, format!("Line {}: Expected {} cells, found {}!", line_no, num_cols, line.len())));
+    }
+    row.fill(0);
+    for (col, _) in line.bytes().enumerate().filter(|(_, c)| *c == b'@') {
+        row[col / 64] |= 1 << (col % 64);
+    }
+    Ok(())
+}
+
+fn accessible_in_row(above: &[u64], cur: &[u64], below: &[u64], threshold: usize, res: &mut [u64]) {
+    // Sets the bits of the rolls in the current row that have fewer than threshold neighbors (rows outside of the grid
+    // are passed as all zeros)
+    let words_per_row = cur.len();
+    for w in 0..words_per_row {
+        // Bit-sliced counter (count = b0 + 2 * b1 + 4 * b2 + 8 * b3 for each of the 64 cells)
+        let (mut b0, mut b1, mut b2, mut b3) = (0u64, 0u64, 0u64, 0u64);
+        let mut add = |x: u64| {
+            let c0 = b0 & x;
+            b0 ^= x;
+            let c1 = b1 & c0;
+            b1 ^= c0;
+            let c2 = b2 & c1;
+            b2 ^= c1;
+            b3 |= c2;
+        };
+
+        for (line, include_center) in [(above, true), (cur, false), (below, true)] {
+            let prev = if w > 0 { line[w - 1] } else { 0 };
+            let next = if w + 1 < words_per_row { line[w + 1] } else { 0 };
+            add((line[w] << 1) | (prev >> 63));  // left neighbor (column - 1)
+            add((line[w] >> 1) | (next << 63));  // right neighbor (column + 1)
+            if include_center {
+                add(line[w]);
+            }
+        }
+
+        // Compare the count with the threshold, starting at the most significant bit
+        let (mut less, mut equal) = (0u64, u64::MAX);
+        for (bit, plane) in [b3, b2, b1, b0].into_iter().enumerate().map(|(i, p)| (3 - i, p)) {
+            if threshold >> bit & 1 == 1 {
+                less |= equal & !plane;
+                equal &= plane;
+            } else {
+                equal &= !plane;
+            }
+        }
+        if threshold > 15 {
+            less = u64::MAX;
+        }
+
+        res[w] = cur[w] & less;
+    }
+}
+
+pub fn remove_accessible_streaming<R: BufRead, W: Write>(input: R, mut output: W, threshold: usize) -> Result<usize> {
+    // One synchronous round of removing all rolls with fewer than threshold neighbors, reading the floor row by row and
+    // writing the updated rows to output (use io::sink() to only count). Only the rows above, at and below the current
+    // one are kept, with their original content, so the memory needed only depends on the width of the floor.
+    let mut lines = input.lines();
+    let Some(first) = lines.next().transpose()? else { return Ok(0) };
+    let num_cols = first.len();
+    let words_per_row = num_cols.div_ceil(64);
+
+    let mut above = vec![0; words_per_row];
+    let mut cur = vec![0; words_per_row];
+    let mut below = vec![0; words_per_row];
+    let mut accessible = vec![0; words_per_row];
+    let mut out_line = vec![b'.'; num_cols + 1];
+    out_line[num_cols] = b'\n';
+    pack_row(&first, 1, num_cols, &mut cur)?;
+
+    let mut removed = 0;
+    for row in 1.. {
+        let next = lines.next().transpose()?;
+        match &next {
+            Some(line) => pack_row(line, row + 1, num_cols, &mut below)?,
+            None => below.fill(0)
+        }
+
+        accessible_in_row(&above, &cur, &below, threshold, &mut accessible);
+        removed += accessible.iter().map(|w| w.count_ones() as usize).sum::<usize>();
+        for (col, c) in out_line[..num_cols].iter_mut().enumerate() {
+            *c = if (cur[col / 64] & !accessible[col / 64]) >> (col % 64) & 1 == 1 { b'@' } else { b'.' };
+        }
+        output.write_all(&out_line)?;
+
+        if next.is_none() {
+            break;
+        }
+        // The current row becomes the row above, the row below the current one (the old row above is overwritten next)
+        std::mem::swap(&mut above, &mut cur);
+        std::mem::swap(&mut cur, &mut below);
+    }
+
+    Ok(removed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::read_input_file;
+    use crate::removal::{simulate_removal, UpdateMode};
+    use crate::rules::{Comparison, Neighborhood, Rules};
+    use crate::test_util::random_fields;
+    use std::fs::{read_to_string, File};
+    use std::io::{sink, BufReader};
+
+    fn to_text(field: &Field) -> String {
+        field.rows_iter().map(|r| r.map(|roll| if *roll { '@' } else { '.' }).chain(['\n']).collect::<String>()).collect()
+    }
+
+    #[test]
+    fn example() {
+        let mut grid = BitGrid::parse(&read_to_string("../inputs/day4_example.txt").unwrap());
+        assert_eq!(grid, BitGrid::from_field(&read_input_file("../inputs/day4_example.txt")));
+        assert_eq!(grid.clone().remove_accessible(4, None).0, 13);
+        assert_eq!(grid.simulate_removal(4).iter().sum::<usize>(), 43);
+        assert_eq!(grid.count_rolls(), 28);
+    }
+
+    #[test]
+    fn matches_dense_field() {
+        // Widths around multiples of 64 to test the carries between words
+        for field in random_fields(40, 30, &[1, 63, 64, 65, 127, 128, 129, 200]) {
+            let grid = BitGrid::from_field(&field);
+            assert_eq!(grid.to_field(), field);
+
+            for threshold in [0, 1, 3, 4, 5, 8, 9] {
+                let cascade = simulate_removal(&field, &Rules::new(&Neighborhood::Moore, Comparison::Less, threshold), UpdateMode::Synchronous);
+                let mut g = grid.clone();
+                assert_eq!(g.simulate_removal(threshold), cascade.rounds, "{} {:?}", threshold, field);
+                assert_eq!(g.to_field(), cascade.remaining);
+
+                // One streamed round is the same as one round on the whole grid
+                let mut output = vec!();
+                let removed = remove_accessible_streaming(to_text(&field).as_bytes(), &mut output, threshold).unwrap();
+                let mut g = grid.clone();
+                assert_eq!(removed, g.remove_accessible(threshold, None).0);
+                assert_eq!(String::from_utf8(output).unwrap(), to_text(&g.to_field()));
+            }
+        }
+    }
+
+    #[test]
+    fn streaming_rounds() {
+        // Feeding the output of a round back in gives the same rounds as the second star
+        let mut floor = vec!();
+        let first_round = remove_accessible_streaming(BufReader::new(File::open("../inputs/day4_example.txt").unwrap()), &mut floor, 4);
+        let mut rounds = vec![first_round.unwrap()];
+        loop {
+            let mut next_floor = vec!();
+            let removed = remove_accessible_streaming(floor.as_slice(), &mut next_floor, 4).unwrap();
+            floor = next_floor;
+            if removed == 0 {
+                break;
+            }
+            rounds.push(removed);
+        }
+        assert_eq!(rounds, vec![13, 12, 7, 5, 2, 1, 1, 1, 1]);
+        assert_eq!(BitGrid::read(floor.as_slice()).unwrap().count_rolls(), 28);
+
+        assert_eq!(remove_accessible_streaming("".as_bytes(), sink(), 4).unwrap(), 0);
+        let err = remove_accessible_streaming("@@\n@@\n@\n".as_bytes(), sink(), 4).unwrap_err();
+        assert_eq!(err.to_string(), "Line 3: Expected 2 cells, found 1!");
+        assert!(BitGrid::read("@@\n@\n".as_bytes()).is_err());
+    }
+
+    #[test]
+    fn wide_streamed_floor() {
+        // About a million columns in 2x2 blocks like in large_grid, the last row only has pairs of rolls (with 1 neighbor
+        // each)
+        let num_cols = 999_999;
+        let block_row: String = (0..num_cols).map(|c| if c % 3 < 2 { '@' } else { '.' }).collect();
+        let empty_row = ".".repeat(num_cols);
+        let floor: String = (0..10).map(|r| if r % 3 < 2 { &block_row } else { &empty_row }).flat_map(|l| [l.as_str(), "\n"]).collect();
+
+        let rolls_per_row = block_row.bytes().filter(|c| *c == b'@').count();
+        assert_eq!(remove_accessible_streaming(floor.as_bytes(), sink(), 4).unwrap(), 7 * rolls_per_row);
+        assert_eq!(remove_accessible_streaming(floor.as_bytes(), sink(), 3).unwrap(), rolls_per_row);
+    }
+
+    #[test]
+    fn large_grid() {
+        // 2x2 blocks separated by empty rows and columns: every roll has exactly 3 neighbors, so everything goes in one round
+        let size = 3000;
+        let mut grid = BitGrid::new(size, size);
+        for row in (0..size).filter(|r| r % 3 < 2) {
+            for col in (0..size).filter(|c| c % 3 < 2) {
+                grid.set(row, col, true);
+            }
+        }
+        assert_eq!(grid.count_rolls(), 2000 * 2000);
+        assert_eq!(grid.clone().simulate_removal(3), vec!());
+        assert_eq!(grid.simulate_removal(4), vec![2000 * 2000]);
+        assert_eq!(grid.count_rolls(), 0);
+    }
+}
@@ -0,0 +1,92 @@
+// Result of a removal simulation: when each roll was removed, how many rolls were removed per round and what is left in
+// the end. The removal rounds can be exported as CSV or as heatmap image to see how the cascade erodes the warehouse.
+
+use array2d::Array2D;
+use std::io::{Result, Write};
+
+use crate::Field;
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Cascade {
+    pub rounds: Vec<usize>,  // number of removed rolls per round
+    pub removal_rounds: Array2D<Option<usize>>,  // 1-based round in which the roll was removed (None for empty cells and the core)
+    pub remaining: Field  // stable core (or what is left after the maximum number of rounds)
+}
+
+impl Cascade {
+    pub fn total_removed(&self) -> usize {
+        self.rounds.iter().sum()
+    }
+
+    pub fn core_size(&self) -> usize {
+        self.remaining.elements_row_major_iter().filter(|r| **r).count()
+    }
+
+    pub fn write_csv<W: Write>(&self, mut writer: W) -> Result<()> {
+        // One line per row of the field: the removal round of each roll, "never" for the core and nothing for empty cells
+        for row in 0..self.remaining.num_rows() {
+            let cells: Vec<String> = (0..self.remaining.num_columns())
+                .map(|col| match self.removal_rounds[(row, col)] {
+                    Some(round) => round.to_string(),
+                    None if self.remaining[(row, col)] => "never".to_string(),
+                    None => String::new()
+                })
+                .collect();
+            writeln!(writer, "{}", cells.join(","))?;
+        }
+        Ok(())
+    }
+
+    pub fn write_heatmap_ppm<W: Write>(&self, mut writer: W) -> Result<()> {
+        // Binary PPM with one pixel per cell: empty cells are black, the core is white and removed rolls go from red (first
+        // round) to blue (last round)
+        let (num_rows, num_cols) = (self.remaining.num_rows(), self.remaining.num_columns());
+        write!(writer, "P6\n{} {}\n255\n", num_cols, num_rows)?;
+
+        let last_round = self.rounds.len().max(2);
+        let mut pixels = Vec::with_capacity(num_rows * num_cols * 3);
+        for row in 0..num_rows {
+            for col in 0..num_cols {
+                let rgb = match self.removal_rounds[(row, col)] {
+                    Some(round) => {
+                        let t = ((round - 1) * 255 / (last_round - 1)) as u8;
+                        [255 - t, 0, t]
+                    }
+                    None if self.remaining[(row, col)] => [255, 255, 255],
+                    None => [0, 0, 0]
+                };
+                pixels.extend_from_slice(&rgb);
+            }
+        }
+
+        writer.write_all(&pixels)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::read_input_file;
+    use crate::removal::{simulate_removal, UpdateMode};
+    use crate::rules::Rules;
+
+    #[test]
+    fn example_exports() {
+        let field = read_input_file("../inputs/day4_example.txt");
+        let cascade = simulate_removal(&field, &Rules::second_star(), UpdateMode::Synchronous);
+        assert_eq!(cascade.total_removed(), 43);
+        assert_eq!(cascade.core_size(), 28);
+
+        let mut csv = vec!();
+        cascade.write_csv(&mut csv).unwrap();
+        let csv = String::from_utf8(csv).unwrap();
+        assert_eq!(csv.lines().count(), 10);
+        assert_eq!(csv.lines().next().unwrap(), ",,1,1,,1,1,2,1,");
+        assert_eq!(csv.lines().nth(3).unwrap(), "2,,6,9,never,never,,,2,");
+
+        let mut ppm = vec!();
+        cascade.write_heatmap_ppm(&mut ppm).unwrap();
+        assert!(ppm.starts_with(b"P6\n10 10\n255\n"));
+        assert_eq!(ppm.len(), "P6\n10 10\n255\n".len() + 300);
+    }
+}
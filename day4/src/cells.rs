@@ -0,0 +1,185 @@
+// Floor plans with different cell types: besides rolls and empty cells, there can be walls/pallets and rolls that can
+// never be removed (e.g., because they are fixed to the floor). Which characters stand for which type, which types count
+// as neighbors and which ones can be removed is configurable.
+
+use array2d::Array2D;
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::cascade::Cascade;
+use crate::removal::{simulate_removal_masked, UpdateMode};
+use crate::rules::Rules;
+use crate::Field;
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Cell {
+    Empty,
+    Roll,
+    FixedRoll,
+    Wall
+}
+
+pub type TypedField = Array2D<Cell>;
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CellMapping {
+    chars: HashMap<char, Cell>
+}
+
+impl Default for CellMapping {
+    fn default() -> Self {
+        CellMapping { chars: HashMap::from([('.', Cell::Empty), ('@', Cell::Roll), ('*', Cell::FixedRoll), ('#', Cell::Wall)]) }
+    }
+}
+
+impl CellMapping {
+    pub fn with(mut self, c: char, cell: Cell) -> Self {
+        self.chars.insert(c, cell);
+        self
+    }
+
+    pub fn parse(&self, input: &str) -> Result<TypedField, CellError> {
+        // Line and column numbers are 1-based, all lines must be as long as the first one
+        let rows = input
+            .lines()
+            .enumerate()
+            .map(|(row, l)| {
+                l.chars()
+                    .enumerate()
+                    .map(|(col, c)| self.chars.get(&c).copied().ok_or(CellError::UnknownCell { line: row + 1, column: col + 1, found: c }))
+                    .collect::<Result<Vec<Cell>, CellError>>()
+            })
+            .collect::<Result<Vec<_>, CellError>>()?;
+
+        if let Some((idx, row)) = rows.iter().enumerate().find(|(_, r)| r.len() != rows[0].len()) {
+            return Err(CellError::RaggedLine { line: idx + 1, len: row.len(), expected: rows[0].len() });
+        }
+        Ok(TypedField::from_rows(&rows).unwrap())
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum CellError {
+    UnknownCell { line: usize, column: usize, found: char },
+    RaggedLine { line: usize, len: usize, expected: usize }
+}
+
+impl fmt::Display for CellError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CellError::UnknownCell { line, column, found } =>
+                write!(f, "Line {}, column {}: Unknown cell '{}'!", line, column, found),
+            CellError::RaggedLine { line, len, expected } =>
+                write!(f, "Line {}: Expected {} cells, found {}!", line, expected, len)
+        }
+    }
+}
+
+impl std::error::Error for CellError {}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CellRules {
+    pub neighbors: Vec<Cell>,  // cell types that count as neighbors (i.e., block access)
+    pub removable: Vec<Cell>
+}
+
+impl Default for CellRules {
+    fn default() -> Self {
+        CellRules { neighbors: vec![Cell::Roll, Cell::FixedRoll, Cell::Wall], removable: vec![Cell::Roll] }
+    }
+}
+
+pub fn simulate_typed_removal(field: &TypedField, rules: &Rules, cell_rules: &CellRules, mode: UpdateMode) -> (Cascade, TypedField) {
+    // Returns the cascade (the remaining field contains the removable cells that are left) and the updated field, in which
+    // the removed cells are empty
+    let mask = |types: &[Cell]| {
+        let rows: Vec<Vec<bool>> = field.rows_iter().map(|r| r.map(|c| types.contains(c)).collect()).collect();
+        Field::from_rows(&rows).unwrap()
+    };
+
+    let vacated_occupied = cell_rules.neighbors.contains(&Cell::Empty);
+    let cascade = simulate_removal_masked(&mask(&cell_rules.neighbors), &mask(&cell_rules.removable), vacated_occupied, rules, mode);
+    let mut updated_field = field.clone();
+    for (pos, round) in cascade.removal_rounds.enumerate_row_major() {
+        if round.is_some() {
+            updated_field[pos] = Cell::Empty;
+        }
+    }
+
+    (cascade, updated_field)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::read_input_file;
+    use crate::removal::simulate_removal;
+    use crate::rules::{Comparison, Neighborhood};
+    use std::fs::read_to_string;
+
+    #[test]
+    fn example_without_special_cells() {
+        let input = read_to_string("../inputs/day4_example.txt").unwrap();
+        let field = CellMapping::default().parse(&input).unwrap();
+        let (cascade, updated_field) = simulate_typed_removal(&field, &Rules::second_star(), &CellRules::default(), UpdateMode::Synchronous);
+        assert_eq!(cascade, simulate_removal(&read_input_file("../inputs/day4_example.txt"), &Rules::second_star(), UpdateMode::Synchronous));
+        assert_eq!(updated_field.elements_row_major_iter().filter(|c| **c == Cell::Roll).count(), 28);
+    }
+
+    #[test]
+    fn walls_and_fixed_rolls() {
+        // Without the walls, everything would be removed
+        let field = CellMapping::default().parse("####\n#@@#\n#@@#\n####\n").unwrap();
+        let (cascade, _) = simulate_typed_removal(&field, &Rules::second_star(), &CellRules::default(), UpdateMode::Synchronous);
+        assert_eq!(cascade.rounds, vec!());
+        let no_walls = CellRules { neighbors: vec![Cell::Roll, Cell::FixedRoll], ..CellRules::default() };
+        let (cascade, updated_field) = simulate_typed_removal(&field, &Rules::second_star(), &no_walls, UpdateMode::Synchronous);
+        assert_eq!(cascade.rounds, vec![4]);
+        assert_eq!(updated_field[(1, 1)], Cell::Empty);
+        assert_eq!(updated_field[(0, 0)], Cell::Wall);
+
+        // Fixed rolls stay, but can be made removable as well
+        let field = CellMapping::default().with('F', Cell::FixedRoll).parse("@@@.\n@F*.\n@@@.\n").unwrap();
+        let (cascade, updated_field) = simulate_typed_removal(&field, &Rules::second_star(), &CellRules::default(), UpdateMode::Synchronous);
+        assert_eq!(cascade.rounds, vec![4, 3]);
+        assert_eq!(updated_field, CellMapping::default().parse("....\n.**.\n....\n").unwrap());
+        let everything = CellRules { removable: vec![Cell::Roll, Cell::FixedRoll], ..CellRules::default() };
+        let (cascade, _) = simulate_typed_removal(&field, &Rules::second_star(), &everything, UpdateMode::Synchronous);
+        assert_eq!(cascade.total_removed(), 9);
+        assert_eq!(cascade.core_size(), 0);
+
+        // Empty cells can also count as neighbors (e.g., for studying the inverse problem)
+        let field = CellMapping::default().parse("...\n.@.\n...\n").unwrap();
+        let empty_blocks = CellRules { neighbors: vec![Cell::Empty], removable: vec![Cell::Roll] };
+        assert_eq!(simulate_typed_removal(&field, &Rules::second_star(), &empty_blocks, UpdateMode::Synchronous).0.rounds, vec!());
+
+        // Removed rolls leave empty cells behind, which still count as neighbors. The corners have 3 neighbors and are
+        // removed, the middle rolls keep their 5 neighbors.
+        let field = CellMapping::default().parse("@@@\n@@@\n").unwrap();
+        let rolls_and_empty = CellRules { neighbors: vec![Cell::Empty, Cell::Roll], removable: vec![Cell::Roll] };
+        for mode in [UpdateMode::Synchronous, UpdateMode::Asynchronous] {
+            let (cascade, updated_field) = simulate_typed_removal(&field, &Rules::second_star(), &rolls_and_empty, mode);
+            assert_eq!(cascade.rounds, vec![4]);
+            assert_eq!(updated_field, CellMapping::default().parse(".@.\n.@.\n").unwrap());
+        }
+
+        // If only empty cells count and rolls next to an empty cell are removable, the removals eat into the row one by one
+        let only_empty = CellRules { neighbors: vec![Cell::Empty], removable: vec![Cell::Roll] };
+        let next_to_empty = Rules::new(&Neighborhood::VonNeumann, Comparison::Greater, 0);
+        let (cascade, updated_field) = simulate_typed_removal(&CellMapping::default().parse("@@@.").unwrap(), &next_to_empty, &only_empty, UpdateMode::Synchronous);
+        assert_eq!(cascade.rounds, vec![1, 1, 1]);
+        assert_eq!(updated_field, CellMapping::default().parse("....").unwrap());
+    }
+
+    #[test]
+    fn invalid_floor_plans() {
+        let err = CellMapping::default().parse("@@@\n@@x\n").unwrap_err();
+        assert_eq!(err, CellError::UnknownCell { line: 2, column: 3, found: 'x' });
+        assert_eq!(err.to_string(), "Line 2, column 3: Unknown cell 'x'!");
+        assert!(CellMapping::default().with('x', Cell::Wall).parse("@@@\n@@x\n").is_ok());
+
+        let err = CellMapping::default().parse("@@@\n@@@\n@.\n").unwrap_err();
+        assert_eq!(err, CellError::RaggedLine { line: 3, len: 2, expected: 3 });
+        assert_eq!(err.to_string(), "Line 3: Expected 3 cells, found 2!");
+    }
+}
@@ -0,0 +1,86 @@
+use array2d::Array2D;
+use std::fs::read_to_string;
+use std::path::Path;
+
+pub mod bitgrid;
+pub mod cascade;
+pub mod cells;
+pub mod removal;
+pub mod rules;
+pub mod sparse;
+#[cfg(test)]
+mod test_util;
+
+use removal::{simulate_removal, UpdateMode};
+use rules::Rules;
+
+pub type Field = Array2D<bool>;
+
+pub fn count_and_remove_accessible_rolls(field: &Field, rules: &Rules) -> (usize, Field) {
+    // Returns the count of accessible rolls and an updated field with them being removed (one round, regardless of the
+    // maximum rounds of the rules)
+    let mut accessible_rolls = 0;
+    let mut updated_field = field.clone();
+
+    let num_rows = field.num_rows();
+    let num_cols = field.num_columns();
+    
+    for row in 0..num_rows {
+        for col in 0..num_cols {
+            if !*field.get(row, col).unwrap() {
+                continue;
+            }
+
+            // Count neighbor rolls (depending on the boundary, also outside of the field)
+            if rules.is_accessible(rules.neighbor_count(field, row, col)) {
+                accessible_rolls += 1;
+                updated_field.set(row, col, false).unwrap();
+            }
+        }
+    }
+
+    (accessible_rolls, updated_field)
+}
+
+#[allow(dead_code)]
+fn print_field(field: &Field) {
+    // For debugging
+    for row in 0..field.num_rows() {
+        for col in 0..field.num_columns() {
+            if *field.get(row, col).unwrap() {
+                print!("x")
+            } else {
+                print!(".")
+            }
+        }
+        println!()
+    }
+}
+
+pub fn read_input_file<P: AsRef<Path>>(input_path: P) -> Field {
+    let input = read_to_string(input_path).expect("Could not read file!");
+    let rows: Vec<Vec<bool>> = input.lines().map(|l| l.chars().map(|c| c == '@').collect()).collect();
+    Field::from_rows(&rows).unwrap()
+}
+
+pub fn count_iteratively_accessible_rolls(initial_field: &Field) -> usize {
+    // Only the rolls next to removed ones are re-examined in each round (instead of rescanning the whole field)
+    simulate_removal(initial_field, &Rules::second_star(), UpdateMode::Synchronous).total_removed()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn example_first_star() {
+        let field = read_input_file("../inputs/day4_example.txt");
+        assert_eq!(count_and_remove_accessible_rolls(&field, &Rules::first_star()).0, 13);
+    }
+
+    #[test]
+    fn example_second_star() {
+        let field = read_input_file("../inputs/day4_example.txt");
+        assert_eq!(count_iteratively_accessible_rolls(&field), 43);
+    }
+}
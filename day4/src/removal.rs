@@ -0,0 +1,189 @@
+// Event-driven removal simulation: Instead of rescanning the whole field each round, we keep the neighbor count of every
+// roll and only re-examine rolls next to a roll that was removed (nothing else can have become accessible).
+
+use array2d::Array2D;
+use std::collections::BTreeSet;
+
+use crate::cascade::Cascade;
+use crate::rules::Rules;
+use crate::Field;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum UpdateMode {
+    Synchronous,  // all rolls accessible at the start of a round are removed together (like the puzzle describes)
+    Asynchronous  // rolls are removed immediately, scanning in row-major order, so later rolls see the removal in the same round
+}
+
+pub fn simulate_removal(field: &Field, rules: &Rules, mode: UpdateMode) -> Cascade {
+    // Removes rolls until nothing is removed anymore or the maximum number of rounds is reached, recording the round in
+    // which each roll is removed. This also works for non-monotonic rules (e.g., "more than n neighbors"), since
+    // the accessibility of a roll can only change when its neighbor count changes.
+    simulate_removal_masked(field, field, false, rules, mode)
+}
+
+pub fn simulate_removal_masked(occupied: &Field, removable: &Field, vacated_occupied: bool, rules: &Rules, mode: UpdateMode) -> Cascade {
+    // Generalization for fields with different cell types: occupied cells count as neighbors, but only removable cells
+    // are ever removed. Whether the cell left behind by a removal counts as neighbor is given by vacated_occupied (e.g.,
+    // if empty cells count as neighbors). The remaining field of the result contains the removable cells that were not
+    // removed.
+    let num_rows = occupied.num_rows();
+    let num_cols = occupied.num_columns();
+    let mut occupied = occupied.clone();
+    let mut removable = removable.clone();
+    let mut counts = Array2D::filled_with(0, num_rows, num_cols);
+    let mut removal_rounds = Array2D::filled_with(None, num_rows, num_cols);
+    let mut candidates = BTreeSet::new();
+
+    for row in 0..num_rows {
+        for col in 0..num_cols {
+            if removable[(row, col)] {
+                counts[(row, col)] = rules.neighbor_count(&occupied, row, col);
+                candidates.insert((row, col));
+            }
+        }
+    }
+
+    let mut rounds = vec!();
+    while !candidates.is_empty() && rules.max_rounds.is_none_or(|m| rounds.len() < m) {
+        let mut next_candidates = BTreeSet::new();
+        let mut removed = 0;
+
+        match mode {
+            UpdateMode::Synchronous => {
+                // Decide on all candidates first, then remove them together
+                let accessible: Vec<_> = candidates.iter().copied().filter(|p| removable[*p] && rules.is_accessible(counts[*p])).collect();
+                let changed: Vec<_> = accessible.iter().copied().filter(|p| occupied[*p] != vacated_occupied).collect();
+                for p in &accessible {
+                    removable[*p] = false;
+                    occupied[*p] = vacated_occupied;
+                    removal_rounds[*p] = Some(rounds.len() + 1);
+                }
+                for &(row, col) in &changed {
+                    for n in rules.reverse_neighbors(row, col, num_rows, num_cols).filter(|n| removable[*n]) {
+                        if vacated_occupied { counts[n] += 1 } else { counts[n] -= 1 }
+                        next_candidates.insert(n);
+                    }
+                }
+                removed = accessible.len();
+            }
+            UpdateMode::Asynchronous => {
+                // Rolls after the current one (in scan order) are still checked in this round, earlier ones in the next
+                while let Some(p) = candidates.pop_first() {
+                    if !removable[p] || !rules.is_accessible(counts[p]) {
+                        continue;
+                    }
+
+                    removable[p] = false;
+                    removal_rounds[p] = Some(rounds.len() + 1);
+                    removed += 1;
+                    if occupied[p] == vacated_occupied {
+                        continue;
+                    }
+
+                    occupied[p] = vacated_occupied;
+                    for n in rules.reverse_neighbors(p.0, p.1, num_rows, num_cols).filter(|n| removable[*n]) {
+                        if vacated_occupied { counts[n] += 1 } else { counts[n] -= 1 }
+                        if n > p { candidates.insert(n); } else { next_candidates.insert(n); }
+                    }
+                }
+            }
+        }
+
+        if removed == 0 {
+            break;
+        }
+        rounds.push(removed);
+        candidates = next_candidates;
+    }
+
+    Cascade { rounds, removal_rounds, remaining: removable }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{count_and_remove_accessible_rolls, read_input_file};
+    use crate::rules::{Boundary, Comparison, Neighborhood};
+    use crate::test_util::random_fields;
+
+    fn rescanning_rounds(field: &Field, rules: &Rules) -> (Vec<usize>, Field) {
+        let mut rounds = vec!();
+        let mut cur_field = field.clone();
+        loop {
+            let (removed, updated_field) = count_and_remove_accessible_rolls(&cur_field, rules);
+            if removed == 0 {
+                return (rounds, cur_field);
+            }
+            rounds.push(removed);
+            cur_field = updated_field;
+        }
+    }
+
+    #[test]
+    fn example_rounds() {
+        let field = read_input_file("../inputs/day4_example.txt");
+        let rounds = simulate_removal(&field, &Rules::second_star(), UpdateMode::Synchronous).rounds;
+        assert_eq!(rounds, vec![13, 12, 7, 5, 2, 1, 1, 1, 1]);
+        assert_eq!(simulate_removal(&field, &Rules::first_star(), UpdateMode::Synchronous).rounds, vec![13]);
+
+        // The same rolls end up being removed, but with fewer rounds
+        let async_cascade = simulate_removal(&field, &Rules::second_star(), UpdateMode::Asynchronous);
+        assert_eq!(async_cascade.total_removed(), 43);
+        assert!(async_cascade.rounds.len() < rounds.len());
+
+        // With only the orthogonal neighbors checked, only fully enclosed rolls survive the first round
+        let von_neumann = Rules::new(&Neighborhood::VonNeumann, Comparison::Less, 4);
+        let cascade = simulate_removal(&field, &von_neumann, UpdateMode::Synchronous);
+        assert_eq!(cascade.rounds, vec![58, 13]);
+        assert_eq!(cascade.core_size(), 0);
+
+        // Rolls at the edges are harder to remove if the field wraps around or the outside counts as filled
+        let toroidal = Rules::second_star().with_boundary(Boundary::Toroidal);
+        let filled = Rules::second_star().with_boundary(Boundary::Filled);
+        assert_eq!(count_and_remove_accessible_rolls(&field, &toroidal).0, 2);
+        assert_eq!(simulate_removal(&field, &toroidal, UpdateMode::Synchronous).rounds, vec![2, 1]);
+        assert_eq!(simulate_removal(&field, &filled, UpdateMode::Synchronous).rounds, vec![1, 1]);
+    }
+
+    #[test]
+    fn matches_rescanning() {
+        let rule_sets = [
+            (Rules::second_star(), true),
+            (Rules::new(&Neighborhood::VonNeumann, Comparison::LessOrEqual, 2), true),
+            (Rules::new(&Neighborhood::ChebyshevRadius(2), Comparison::Less, 10), true),
+            (Rules::new(&Neighborhood::ManhattanRadius(2), Comparison::Less, 5).with_max_rounds(3), false),
+            (Rules::new(&Neighborhood::Custom(vec![(0, 1), (1, 1), (2, -1)]), Comparison::Less, 2), true),
+            (Rules::new(&Neighborhood::Moore, Comparison::Greater, 5), false),
+            (Rules::new(&Neighborhood::Moore, Comparison::Equal, 3), false),
+            (Rules::second_star().with_boundary(Boundary::Toroidal), true),
+            (Rules::second_star().with_boundary(Boundary::Filled), true),
+            (Rules::new(&Neighborhood::ChebyshevRadius(2), Comparison::Less, 12).with_boundary(Boundary::Toroidal), true),
+            (Rules::new(&Neighborhood::Custom(vec![(0, 1), (1, 1), (2, -1)]), Comparison::Less, 2).with_boundary(Boundary::Filled), true)
+        ];
+
+        let widths: Vec<usize> = (1..=40).collect();
+        for field in random_fields(50, 40, &widths) {
+            for (rules, monotonic) in &rule_sets {
+                let (mut rounds, mut remaining) = rescanning_rounds(&field, rules);
+                if let Some(m) = rules.max_rounds.filter(|m| *m < rounds.len()) {
+                    rounds.truncate(m);
+                    remaining = (0..m).fold(field.clone(), |f, _| count_and_remove_accessible_rolls(&f, rules).1);
+                }
+                let cascade = simulate_removal(&field, rules, UpdateMode::Synchronous);
+                assert_eq!((&cascade.rounds, &cascade.remaining), (&rounds, &remaining), "{:?}", rules);
+
+                // The removal rounds add up to the per-round counts
+                for (idx, count) in rounds.iter().enumerate() {
+                    assert_eq!(cascade.removal_rounds.elements_row_major_iter().filter(|r| **r == Some(idx + 1)).count(), *count);
+                }
+
+                // For monotonic rules, the removal order does not matter for the end result
+                if *monotonic {
+                    let async_cascade = simulate_removal(&field, rules, UpdateMode::Asynchronous);
+                    assert_eq!(async_cascade.total_removed(), cascade.total_removed());
+                    assert_eq!(async_cascade.remaining, remaining);
+                }
+            }
+        }
+    }
+}
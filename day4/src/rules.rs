@@ -0,0 +1,183 @@
+// Rules deciding which rolls are accessible: the neighborhood that is checked around each roll, how the number of
+// neighboring rolls is compared to a threshold and how many rounds of removal are done. The puzzle uses the 8 surrounding
+// cells and "fewer than 4 neighbors", once for the first star and until nothing changes for the second one.
+
+use std::cmp::Ordering;
+
+use crate::Field;
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Neighborhood {
+    VonNeumann,  // 4 orthogonally adjacent cells
+    Moore,  // 8 surrounding cells
+    ManhattanRadius(usize),
+    ChebyshevRadius(usize),
+    Custom(Vec<(isize, isize)>)  // (row, col) offsets, (0, 0) is ignored
+}
+
+impl Neighborhood {
+    pub fn offsets(&self) -> Vec<(isize, isize)> {
+        let square = |r: usize| {
+            let r = r as isize;
+            (-r..=r).flat_map(move |dr| (-r..=r).map(move |dc| (dr, dc)))
+        };
+
+        let mut res: Vec<(isize, isize)> = match self {
+            Neighborhood::VonNeumann => return Neighborhood::ManhattanRadius(1).offsets(),
+            Neighborhood::Moore => return Neighborhood::ChebyshevRadius(1).offsets(),
+            Neighborhood::ManhattanRadius(r) => square(*r).filter(|(dr, dc)| dr.unsigned_abs() + dc.unsigned_abs() <= *r).collect(),
+            Neighborhood::ChebyshevRadius(r) => square(*r).collect(),
+            Neighborhood::Custom(offsets) => offsets.clone()
+        };
+
+        res.sort_unstable();
+        res.dedup();
+        res.retain(|o| *o != (0, 0));
+        res
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Comparison {
+    Less,
+    LessOrEqual,
+    Equal,
+    GreaterOrEqual,
+    Greater
+}
+
+impl Comparison {
+    pub fn holds(self, count: usize, threshold: usize) -> bool {
+        let ord = count.cmp(&threshold);
+        match self {
+            Comparison::Less => ord == Ordering::Less,
+            Comparison::LessOrEqual => ord != Ordering::Greater,
+            Comparison::Equal => ord == Ordering::Equal,
+            Comparison::GreaterOrEqual => ord != Ordering::Less,
+            Comparison::Greater => ord == Ordering::Greater
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Boundary {
+    Clamp,  // cells outside of the field do not exist, so edge rolls have fewer neighbors
+    Toroidal,  // the field wraps around at the edges
+    Filled  // cells outside of the field count as (never removed) rolls
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Rules {
+    offsets: Vec<(isize, isize)>,
+    pub comparison: Comparison,
+    pub threshold: usize,
+    pub max_rounds: Option<usize>,  // None removes rolls until nothing changes anymore
+    pub boundary: Boundary
+}
+
+impl Rules {
+    pub fn new(neighborhood: &Neighborhood, comparison: Comparison, threshold: usize) -> Self {
+        Rules { offsets: neighborhood.offsets(), comparison, threshold, max_rounds: None, boundary: Boundary::Clamp }
+    }
+
+    pub fn first_star() -> Self {
+        Rules::new(&Neighborhood::Moore, Comparison::Less, 4).with_max_rounds(1)
+    }
+
+    pub fn second_star() -> Self {
+        Rules::new(&Neighborhood::Moore, Comparison::Less, 4)
+    }
+
+    pub fn with_max_rounds(mut self, max_rounds: usize) -> Self {
+        self.max_rounds = Some(max_rounds);
+        self
+    }
+
+    pub fn with_boundary(mut self, boundary: Boundary) -> Self {
+        self.boundary = boundary;
+        self
+    }
+
+    pub fn offsets(&self) -> &[(isize, isize)] {
+        &self.offsets
+    }
+
+    pub fn is_accessible(&self, neighbor_count: usize) -> bool {
+        self.comparison.holds(neighbor_count, self.threshold)
+    }
+
+    pub fn neighbor_count(&self, field: &Field, row: usize, col: usize) -> usize {
+        let (num_rows, num_cols) = (field.num_rows(), field.num_columns());
+        let inside = self.neighbors(row, col, num_rows, num_cols).filter(|p| field[*p]).count();
+        let outside = match self.boundary {
+            Boundary::Filled => self.offsets.len() - self.neighbors(row, col, num_rows, num_cols).count(),
+            _ => 0
+        };
+        inside + outside
+    }
+
+    pub fn neighbors(&self, row: usize, col: usize, num_rows: usize, num_cols: usize) -> impl Iterator<Item = (usize, usize)> + '_ {
+        // Cells within the field whose roll counts as neighbor of (row, col). With wrap-around, a cell can show up
+        // multiple times (or even be its own neighbor) if the neighborhood is larger than the field, it is then counted
+        // once per offset.
+        self.offsets.iter().filter_map(move |(dr, dc)| self.offset_pos(row, col, *dr, *dc, num_rows, num_cols))
+    }
+
+    pub fn reverse_neighbors(&self, row: usize, col: usize, num_rows: usize, num_cols: usize) -> impl Iterator<Item = (usize, usize)> + '_ {
+        // Cells that have (row, col) as neighbor (differs from the neighbors for asymmetric custom neighborhoods)
+        self.offsets.iter().filter_map(move |(dr, dc)| self.offset_pos(row, col, -dr, -dc, num_rows, num_cols))
+    }
+
+    fn offset_pos(&self, row: usize, col: usize, dr: isize, dc: isize, num_rows: usize, num_cols: usize) -> Option<(usize, usize)> {
+        if self.boundary == Boundary::Toroidal {
+            let wrap = |v: usize, d: isize, n: usize| (v as isize + d).rem_euclid(n as isize) as usize;
+            return Some((wrap(row, dr, num_rows), wrap(col, dc, num_cols)));
+        }
+
+        let r = row.checked_add_signed(dr).filter(|r| *r < num_rows)?;
+        let c = col.checked_add_signed(dc).filter(|c| *c < num_cols)?;
+        Some((r, c))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn neighborhood_offsets() {
+        assert_eq!(Neighborhood::VonNeumann.offsets(), vec![(-1, 0), (0, -1), (0, 1), (1, 0)]);
+        assert_eq!(Neighborhood::Moore.offsets().len(), 8);
+        assert_eq!(Neighborhood::ChebyshevRadius(2).offsets().len(), 24);
+        assert_eq!(Neighborhood::ManhattanRadius(2).offsets().len(), 12);
+        assert_eq!(Neighborhood::Custom(vec![(0, 1), (0, 0), (0, 1), (2, -1)]).offsets(), vec![(0, 1), (2, -1)]);
+
+        let rules = Rules::new(&Neighborhood::Custom(vec![(0, 1), (1, 1)]), Comparison::Less, 1);
+        assert_eq!(rules.neighbors(0, 0, 2, 2).collect::<Vec<_>>(), vec![(0, 1), (1, 1)]);
+        assert_eq!(rules.reverse_neighbors(0, 0, 2, 2).count(), 0);
+        assert_eq!(rules.reverse_neighbors(1, 1, 2, 2).collect::<Vec<_>>(), vec![(1, 0), (0, 0)]);
+    }
+
+    #[test]
+    fn boundaries() {
+        let field = Field::from_rows(&[vec![true, false, true], vec![false, true, true]]).unwrap();
+        let rules = Rules::new(&Neighborhood::Moore, Comparison::Less, 4);
+        assert_eq!(rules.neighbor_count(&field, 0, 0), 1);
+        assert_eq!(rules.clone().with_boundary(Boundary::Filled).neighbor_count(&field, 0, 0), 6);
+
+        // Wrapping around in a 2x3 field reaches the other row twice (above and below)
+        let toroidal = rules.with_boundary(Boundary::Toroidal);
+        assert_eq!(toroidal.neighbors(0, 0, 2, 3).count(), 8);
+        assert_eq!(toroidal.neighbor_count(&field, 0, 0), 5);
+        assert_eq!(toroidal.reverse_neighbors(0, 0, 2, 3).filter(|p| *p == (1, 2)).count(), 2);
+    }
+
+    #[test]
+    fn comparisons() {
+        assert!(Comparison::Less.holds(3, 4) && !Comparison::Less.holds(4, 4));
+        assert!(Comparison::LessOrEqual.holds(4, 4) && !Comparison::LessOrEqual.holds(5, 4));
+        assert!(Comparison::Equal.holds(4, 4) && !Comparison::Equal.holds(3, 4));
+        assert!(Comparison::GreaterOrEqual.holds(4, 4) && !Comparison::GreaterOrEqual.holds(3, 4));
+        assert!(Comparison::Greater.holds(5, 4) && !Comparison::Greater.holds(4, 4));
+    }
+}
@@ -0,0 +1,239 @@
+// Sparse representation for huge but mostly empty floors: only the coordinates of the rolls are stored, so the floor is
+// unbounded (and coordinates may be negative). Since everything outside of a dense field is empty as well, the results
+// are the same as for the dense field with clamped boundaries. Other boundary policies have no meaning here and are
+// rejected.
+
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::fmt;
+use std::fs::read_to_string;
+use std::path::Path;
+
+use crate::removal::UpdateMode;
+use crate::rules::{Boundary, Rules};
+use crate::Field;
+
+type Pos = (i64, i64);
+pub type SparseField = HashSet<Pos>;
+
+pub fn sparse_from_field(field: &Field) -> SparseField {
+    field
+        .enumerate_row_major()
+        .filter(|(_, roll)| **roll)
+        .map(|((row, col), _)| (row as i64, col as i64))
+        .collect()
+}
+
+fn neighbors<'a>(rules: &'a Rules, (row, col): Pos) -> impl Iterator<Item = Pos> + 'a {
+    // Positions beyond the range of i64 do not exist (like the cells outside of a clamped dense field)
+    rules.offsets().iter().filter_map(move |(dr, dc)| Some((row.checked_add(*dr as i64)?, col.checked_add(*dc as i64)?)))
+}
+
+fn reverse_neighbors<'a>(rules: &'a Rules, (row, col): Pos) -> impl Iterator<Item = Pos> + 'a {
+    rules.offsets().iter().filter_map(move |(dr, dc)| Some((row.checked_sub(*dr as i64)?, col.checked_sub(*dc as i64)?)))
+}
+
+fn check_boundary(rules: &Rules) {
+    assert_eq!(rules.boundary, Boundary::Clamp, "Sparse fields are unbounded, only the clamp boundary is supported!");
+}
+
+pub fn count_and_remove_accessible_rolls_sparse(field: &SparseField, rules: &Rules) -> (usize, SparseField) {
+    // One round of removal, like for the dense field
+    check_boundary(rules);
+    let updated_field: SparseField = field
+        .iter()
+        .copied()
+        .filter(|p| !rules.is_accessible(neighbors(rules, *p).filter(|n| field.contains(n)).count()))
+        .collect();
+    (field.len() - updated_field.len(), updated_field)
+}
+
+pub fn simulate_removal_sparse(field: &SparseField, rules: &Rules, mode: UpdateMode) -> (Vec<usize>, SparseField) {
+    // Same event-driven approach as for the dense field, with the neighbor counts kept in a hash map. Returns the number
+    // of removed rolls per round and the remaining rolls.
+    check_boundary(rules);
+    let mut field = field.clone();
+    let mut counts: HashMap<Pos, usize> = field.iter().map(|p| (*p, neighbors(rules, *p).filter(|n| field.contains(n)).count())).collect();
+    let mut candidates: BTreeSet<Pos> = field.iter().copied().collect();  // ordered to scan in row-major order
+
+    let mut rounds = vec!();
+    while !candidates.is_empty() && rules.max_rounds.is_none_or(|m| rounds.len() < m) {
+        let mut next_candidates = BTreeSet::new();
+        let mut removed = 0;
+
+        match mode {
+            UpdateMode::Synchronous => {
+                let accessible: Vec<Pos> = candidates.iter().copied().filter(|p| field.contains(p) && rules.is_accessible(counts[p])).collect();
+                for p in &accessible {
+                    field.remove(p);
+                    counts.remove(p);
+                }
+                for p in &accessible {
+                    for n in reverse_neighbors(rules, *p).filter(|n| field.contains(n)) {
+                        *counts.get_mut(&n).unwrap() -= 1;
+                        next_candidates.insert(n);
+                    }
+                }
+                removed = accessible.len();
+            }
+            UpdateMode::Asynchronous => {
+                while let Some(p) = candidates.pop_first() {
+                    if !field.contains(&p) || !rules.is_accessible(counts[&p]) {
+                        continue;
+                    }
+
+                    field.remove(&p);
+                    counts.remove(&p);
+                    removed += 1;
+                    for n in reverse_neighbors(rules, p).filter(|n| field.contains(n)) {
+                        *counts.get_mut(&n).unwrap() -= 1;
+                        if n > p { candidates.insert(n); } else { next_candidates.insert(n); }
+                    }
+                }
+            }
+        }
+
+        if removed == 0 {
+            break;
+        }
+        rounds.push(removed);
+        candidates = next_candidates;
+    }
+
+    (rounds, field)
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum CoordinateError {
+    MissingComma { line: usize },
+    InvalidCoordinate { line: usize, column: usize, found: String }
+}
+
+impl fmt::Display for CoordinateError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CoordinateError::MissingComma { line } =>
+                write!(f, "Line {}: Expected \"row,col\"!", line),
+            CoordinateError::InvalidCoordinate { line, column, found } =>
+                write!(f, "Line {}, column {}: Invalid coordinate \"{}\"!", line, column, found)
+        }
+    }
+}
+
+impl std::error::Error for CoordinateError {}
+
+pub fn parse_coordinates(input: &str) -> Result<SparseField, CoordinateError> {
+    // One roll per line as "row,col", empty lines and lines starting with '#' are ignored. Line and column numbers are
+    // 1-based.
+    input
+        .lines()
+        .enumerate()
+        .filter(|(_, l)| !l.trim().is_empty() && !l.trim().starts_with('#'))
+        .map(|(idx, l)| {
+            let line = idx + 1;
+            let (row, col) = l.split_once(',').ok_or(CoordinateError::MissingComma { line })?;
+            let parse = |s: &str, offset: usize| {
+                let column = offset + s.len() - s.trim_start().len() + 1;
+                s.trim().parse().map_err(|_| CoordinateError::InvalidCoordinate { line, column, found: s.trim().to_string() })
+            };
+            Ok((parse(row, 0)?, parse(col, row.len() + 1)?))
+        })
+        .collect()
+}
+
+pub fn read_coordinate_file<P: AsRef<Path>>(input_path: P) -> Result<SparseField, CoordinateError> {
+    parse_coordinates(&read_to_string(input_path).expect("Could not read file!"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::removal::simulate_removal;
+    use crate::rules::{Comparison, Neighborhood};
+    use crate::{count_and_remove_accessible_rolls, read_input_file};
+
+    #[test]
+    fn example_coordinates() {
+        let field = read_coordinate_file("../inputs/day4_example_coords.txt").unwrap();
+        assert_eq!(field.len(), 71);
+        assert!(field.contains(&(-4, -5)));
+
+        assert_eq!(count_and_remove_accessible_rolls_sparse(&field, &Rules::first_star()).0, 13);
+        let (rounds, remaining) = simulate_removal_sparse(&field, &Rules::second_star(), UpdateMode::Synchronous);
+        assert_eq!(rounds.iter().sum::<usize>(), 43);
+        assert_eq!(remaining.len(), 28);
+
+        // A few rolls far apart are all accessible right away
+        let scattered = parse_coordinates("-1000000000,5\n0,0\n0,1\n# comment\n\n123456789012, -42\n").unwrap();
+        assert_eq!(simulate_removal_sparse(&scattered, &Rules::second_star(), UpdateMode::Synchronous).0, vec![4]);
+    }
+
+    #[test]
+    fn matches_dense_field() {
+        let field = read_input_file("../inputs/day4_example.txt");
+        let sparse = sparse_from_field(&field);
+        let shifted: SparseField = sparse.iter().map(|(r, c)| (r - 4, c - 7)).collect();
+        assert_eq!(shifted, read_coordinate_file("../inputs/day4_example_coords.txt").unwrap());
+
+        let rule_sets = [
+            Rules::first_star(),
+            Rules::second_star(),
+            Rules::new(&Neighborhood::VonNeumann, Comparison::Less, 3),
+            Rules::new(&Neighborhood::ChebyshevRadius(2), Comparison::Less, 9),
+            Rules::new(&Neighborhood::Custom(vec![(0, 1), (1, 1), (2, -1)]), Comparison::Less, 2),
+            Rules::new(&Neighborhood::Moore, Comparison::Greater, 4)
+        ];
+
+        for rules in &rule_sets {
+            let (count, updated_field) = count_and_remove_accessible_rolls(&field, rules);
+            assert_eq!(count_and_remove_accessible_rolls_sparse(&shifted, rules), (count, sparse_from_field(&updated_field).iter().map(|(r, c)| (r - 4, c - 7)).collect()));
+
+            for mode in [UpdateMode::Synchronous, UpdateMode::Asynchronous] {
+                let cascade = simulate_removal(&field, rules, mode);
+                let (rounds, remaining) = simulate_removal_sparse(&sparse, rules, mode);
+                assert_eq!(rounds, cascade.rounds, "{:?} {:?}", rules, mode);
+                assert_eq!(remaining, sparse_from_field(&cascade.remaining));
+                assert_eq!(simulate_removal_sparse(&shifted, rules, mode).0, cascade.rounds);
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "only the clamp boundary is supported")]
+    fn toroidal_boundary_rejected() {
+        simulate_removal_sparse(&parse_coordinates("0,0").unwrap(), &Rules::second_star().with_boundary(Boundary::Toroidal), UpdateMode::Synchronous);
+    }
+
+    #[test]
+    #[should_panic(expected = "only the clamp boundary is supported")]
+    fn filled_boundary_rejected() {
+        count_and_remove_accessible_rolls_sparse(&parse_coordinates("0,0").unwrap(), &Rules::first_star().with_boundary(Boundary::Filled));
+    }
+
+    #[test]
+    fn invalid_coordinates() {
+        let err = parse_coordinates("1,2\nx,3\n").unwrap_err();
+        assert_eq!(err, CoordinateError::InvalidCoordinate { line: 2, column: 1, found: "x".to_string() });
+        assert_eq!(err.to_string(), "Line 2, column 1: Invalid coordinate \"x\"!");
+        assert_eq!(parse_coordinates("# comment\n\n5, 99999999999999999999\n").unwrap_err().to_string(), "Line 3, column 4: Invalid coordinate \"99999999999999999999\"!");
+        assert_eq!(parse_coordinates("1,2\n3\n").unwrap_err(), CoordinateError::MissingComma { line: 2 });
+    }
+
+    #[test]
+    fn coordinates_at_the_limits() {
+        // Rolls at the edges of the i64 range behave like rolls at the edges of a clamped dense field
+        let field = read_input_file("../inputs/day4_example.txt");
+        let sparse = sparse_from_field(&field);
+        let cascade = simulate_removal(&field, &Rules::second_star(), UpdateMode::Synchronous);
+        let (max_row, max_col) = (field.num_rows() as i64 - 1, field.num_columns() as i64 - 1);
+
+        let bottom_right: SparseField = sparse.iter().map(|(r, c)| (i64::MAX - max_row + r, i64::MAX - max_col + c)).collect();
+        let top_left: SparseField = sparse.iter().map(|(r, c)| (i64::MIN + r, i64::MIN + c)).collect();
+        for shifted in [bottom_right, top_left] {
+            assert_eq!(count_and_remove_accessible_rolls_sparse(&shifted, &Rules::first_star()).0, 13);
+            for mode in [UpdateMode::Synchronous, UpdateMode::Asynchronous] {
+                assert_eq!(simulate_removal_sparse(&shifted, &Rules::second_star(), mode).0, simulate_removal(&field, &Rules::second_star(), mode).rounds);
+            }
+        }
+        assert_eq!(cascade.total_removed(), 43);
+    }
+}
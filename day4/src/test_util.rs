@@ -0,0 +1,21 @@
+// Helpers shared by the tests of several modules
+
+use crate::Field;
+
+pub fn random_fields(count: usize, max_rows: u64, widths: &[usize]) -> Vec<Field> {
+    // Simple linear congruential generator, with varying fill rates to get both sparse and dense fields. The number of
+    // rows is between 1 and max_rows, the number of columns is picked from the given widths.
+    let mut state: u64 = 0x2025;
+    let mut next = move |m: u64| {
+        state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+        (state >> 33) % m
+    };
+
+    (0..count)
+        .map(|_| {
+            let (num_rows, num_cols, fill) = (1 + next(max_rows) as usize, widths[next(widths.len() as u64) as usize], 1 + next(10));
+            let rows: Vec<Vec<bool>> = (0..num_rows).map(|_| (0..num_cols).map(|_| next(10) < fill).collect()).collect();
+            Field::from_rows(&rows).unwrap()
+        })
+        .collect()
+}
@@ -0,0 +1,108 @@
+use std::fs::read_to_string;
+use std::path::Path;
+
+pub mod range_set;
+
+use range_set::RangeSet;
+
+#[derive(Debug, Eq, PartialEq, Ord, PartialOrd)]
+enum PosType {
+    Start,
+    End
+}
+
+#[derive(Debug, Eq, PartialEq)]
+struct PosWithType {
+    p: u64,
+    t: PosType
+}
+
+impl PartialOrd for PosWithType { fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> { Some(self.cmp(other)) } }
+impl Ord for PosWithType {
+    // It is important that start positions come before stop positions (position being the same).
+    // Otherwise, we would be counting the position twice in case one range ends where another starts.
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering { self.p.cmp(&other.p).then(self.t.cmp(&other.t)) }
+}
+
+fn merge_ranges(fresh_ranges: &[(u64, u64)]) -> Vec<(u64, u64)> {
+    // Ranges can overlap arbitrarily. We build a list of all start/end positions of all the ranges and iterate it to find the merged ranges of positions that are in at least one list.
+    let mut positions: Vec<PosWithType> = fresh_ranges
+        .iter()
+        .flat_map(|(start, end)| [
+            PosWithType { p: *start, t: PosType::Start },
+            PosWithType { p: *end, t: PosType::End }
+        ])
+        .collect();
+    positions.sort();  // We use a list instead of set because there can be duplicate elements (and I'm too lazy to use a multiset)
+
+    let mut active_ranges = 0;
+    let mut merged_ranges = vec!();
+    let mut start_active_range = 0;
+
+    for cur in positions {
+        match cur.t {
+            PosType::Start => {
+                if active_ranges == 0 {
+                    start_active_range = cur.p;
+                }
+                active_ranges += 1;
+            }
+            PosType::End => {
+                assert!(active_ranges > 0);
+                active_ranges -= 1;
+                if active_ranges == 0 {
+                    merged_ranges.push((start_active_range, cur.p));
+                }
+            }
+        }
+    }
+
+    merged_ranges
+}
+
+pub fn calc_considered_fresh_count(fresh_ranges: &[(u64, u64)]) -> u64 {
+    merge_ranges(fresh_ranges).iter().map(|(start, end)| end - start + 1).sum()
+}
+
+pub fn count_fresh_ingredients(fresh_ranges: &[(u64, u64)], ingredients: &[u64]) -> usize {
+    // Binary search in the merged ranges instead of checking every range for every ingredient. Ranges with start after end
+    // contain no IDs and are ignored.
+    let valid_ranges: Vec<(u64, u64)> = fresh_ranges.iter().filter(|(start, end)| start <= end).copied().collect();
+    let fresh = RangeSet::from_ranges(&valid_ranges);
+    ingredients.iter().filter(|i| fresh.contains(**i)).count()
+}
+
+pub fn read_input_file<P: AsRef<Path>>(input_path: P) -> (Vec<(u64, u64)>, Vec<u64>) {
+    let input = read_to_string(input_path).expect("Could not read file!");
+    let mut fresh_ranges = vec!();
+    let mut ingredients = vec!();
+
+    for line in input.lines() {
+        if line.contains("-") {
+            let s = line.split_once("-").unwrap();
+            fresh_ranges.push((s.0.parse().unwrap(), s.1.parse().unwrap()));
+        } else if !line.is_empty() {
+            ingredients.push(line.parse().unwrap());
+        }
+    }
+    
+    (fresh_ranges, ingredients)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn example_first_star() {
+        let (fresh_ranges, ingredients) = read_input_file("../inputs/day5_example.txt");
+        assert_eq!(count_fresh_ingredients(&fresh_ranges, &ingredients), 3);
+        assert_eq!(count_fresh_ingredients(&[(3, 5), (20, 10)], &[4, 15]), 1);
+    }
+
+    #[test]
+    fn example_second_star() {
+        let (fresh_ranges, _) = read_input_file("../inputs/day5_example.txt");
+        assert_eq!(calc_considered_fresh_count(&fresh_ranges), 14);
+    }
+}
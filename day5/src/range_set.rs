@@ -0,0 +1,238 @@
+// Set of IDs stored as sorted, disjoint inclusive ranges. The ranges are kept maximal, i.e., adjacent ranges like 3-5 and
+// 6-8 are merged into 3-8 as well, so two sets with the same IDs always have the same ranges.
+
+use crate::merge_ranges;
+
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct RangeSet {
+    ranges: Vec<(u64, u64)>
+}
+
+impl RangeSet {
+    pub fn new() -> Self {
+        RangeSet { ranges: vec!() }
+    }
+
+    pub fn from_ranges(ranges: &[(u64, u64)]) -> Self {
+        // The sweep merges overlapping ranges, afterwards we only need to join adjacent ones
+        assert!(ranges.iter().all(|(start, end)| start <= end), "Range with start after end!");
+        let mut res: Vec<(u64, u64)> = Vec::new();
+        for (start, end) in merge_ranges(ranges) {
+            match res.last_mut() {
+                Some(last) if last.1.checked_add(1) == Some(start) => last.1 = end,
+                _ => res.push((start, end))
+            }
+        }
+        RangeSet { ranges: res }
+    }
+
+    pub fn ranges(&self) -> &[(u64, u64)] {
+        &self.ranges
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (u64, u64)> + '_ {
+        self.ranges.iter().copied()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    pub fn cardinality(&self) -> u128 {
+        // u128 since the set of all u64 values has 2^64 elements
+        self.ranges.iter().map(|(start, end)| (end - start) as u128 + 1).sum()
+    }
+
+    pub fn contains(&self, id: u64) -> bool {
+        // Index of the first range that starts after the ID, the range before is the only one that can contain it
+        let idx = self.ranges.partition_point(|(start, _)| *start <= id);
+        idx > 0 && self.ranges[idx - 1].1 >= id
+    }
+
+    pub fn insert(&mut self, start: u64, end: u64) {
+        // Only the ranges that overlap or touch [start, end] are affected, they are replaced by a single merged range. A
+        // range with start after end is empty.
+        if start > end {
+            return;
+        }
+        let lo = self.ranges.partition_point(|(_, e)| *e < start.saturating_sub(1));
+        let hi = self.ranges.partition_point(|(s, _)| *s <= end.saturating_add(1));
+        let merged = match self.ranges.get(lo..hi) {
+            Some([first, .., last]) | Some([first @ last]) => (first.0.min(start), last.1.max(end)),
+            _ => (start, end)
+        };
+        self.ranges.splice(lo..hi, [merged]);
+    }
+
+    pub fn remove(&mut self, start: u64, end: u64) {
+        // Ranges that overlap [start, end] are replaced by the parts sticking out on the left and right (if any)
+        if start > end {
+            return;
+        }
+        let lo = self.ranges.partition_point(|(_, e)| *e < start);
+        let hi = self.ranges.partition_point(|(s, _)| *s <= end);
+        if lo == hi {
+            return;
+        }
+        let (first, last) = (self.ranges[lo], self.ranges[hi - 1]);
+        let left = (first.0 < start).then(|| (first.0, start - 1));
+        let right = (last.1 > end).then(|| (end + 1, last.1));
+        self.ranges.splice(lo..hi, left.into_iter().chain(right));
+    }
+
+    pub fn union(&self, other: &RangeSet) -> RangeSet {
+        RangeSet::from_ranges(&[self.ranges.as_slice(), other.ranges.as_slice()].concat())
+    }
+
+    pub fn intersection(&self, other: &RangeSet) -> RangeSet {
+        // Both lists are sorted, so we can walk through them in parallel and always advance the range that ends first
+        let mut res = vec!();
+        let (mut i, mut j) = (0, 0);
+        while i < self.ranges.len() && j < other.ranges.len() {
+            let (a, b) = (self.ranges[i], other.ranges[j]);
+            let (start, end) = (a.0.max(b.0), a.1.min(b.1));
+            if start <= end {
+                res.push((start, end));
+            }
+            if a.1 < b.1 { i += 1 } else { j += 1 }
+        }
+        RangeSet { ranges: res }
+    }
+
+    pub fn complement(&self, lower: u64, upper: u64) -> RangeSet {
+        // All IDs in [lower, upper] that are not in the set
+        let mut res = vec!();
+        let mut next = Some(lower);  // first ID that might still be missing (None if we passed u64::MAX)
+        for (start, end) in self.iter().filter(|(start, end)| *end >= lower && *start <= upper) {
+            if let Some(n) = next.filter(|n| *n < start) {
+                res.push((n, start - 1));
+            }
+            next = end.checked_add(1);
+        }
+        if let Some(n) = next.filter(|n| *n <= upper && lower <= upper) {
+            res.push((n, upper));
+        }
+        RangeSet { ranges: res }
+    }
+
+    pub fn difference(&self, other: &RangeSet) -> RangeSet {
+        self.intersection(&other.complement(0, u64::MAX))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::read_input_file;
+
+    fn to_mask(set: &RangeSet) -> u64 {
+        // Sets within 0..64 as bit masks to compare with brute force set operations
+        set.iter().flat_map(|(start, end)| start..=end).fold(0, |acc, id| acc | (1 << id))
+    }
+
+    fn random_sets() -> Vec<RangeSet> {
+        let mut state: u64 = 0x2025;
+        let mut next = move |m: u64| {
+            state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+            (state >> 33) % m
+        };
+
+        (0..60)
+            .map(|_| {
+                let ranges: Vec<(u64, u64)> = (0..next(6))
+                    .map(|_| {
+                        let start = next(64);
+                        (start, (start + next(12)).min(63))
+                    })
+                    .collect();
+                RangeSet::from_ranges(&ranges)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn example_ranges() {
+        let (fresh_ranges, ingredients) = read_input_file("../inputs/day5_example.txt");
+        let set = RangeSet::from_ranges(&fresh_ranges);
+        assert_eq!(set.ranges(), &[(3, 5), (10, 20)]);
+        assert_eq!(set.cardinality(), 14);
+        assert_eq!(ingredients.iter().filter(|i| set.contains(**i)).count(), 3);
+
+        let mut set = set;
+        set.insert(6, 9);
+        assert_eq!(set.ranges(), &[(3, 20)]);
+        set.remove(8, 8);
+        set.remove(20, 100);
+        assert_eq!(set.ranges(), &[(3, 7), (9, 19)]);
+        assert_eq!(set.complement(0, 25).ranges(), &[(0, 2), (8, 8), (20, 25)]);
+        assert_eq!(set.complement(5, 9).ranges(), &[(8, 8)]);
+    }
+
+    #[test]
+    fn matches_brute_force() {
+        let sets = random_sets();
+        for a in &sets {
+            // Canonical form: sorted, disjoint and not adjacent
+            assert!(a.ranges().windows(2).all(|w| w[0].1 + 1 < w[1].0));
+            assert_eq!(a.cardinality(), to_mask(a).count_ones() as u128);
+            assert!((0..64).all(|id| a.contains(id) == (to_mask(a) & (1 << id) != 0)));
+            assert_eq!(to_mask(&a.complement(0, 63)), !to_mask(a));
+            assert_eq!(to_mask(&a.complement(10, 20)), !to_mask(a) & (((1 << 11) - 1) << 10));
+
+            for b in sets.iter().take(20) {
+                assert_eq!(to_mask(&a.union(b)), to_mask(a) | to_mask(b));
+                assert_eq!(to_mask(&a.intersection(b)), to_mask(a) & to_mask(b));
+                assert_eq!(to_mask(&a.difference(b)), to_mask(a) & !to_mask(b));
+                assert_eq!(a.union(b), RangeSet::from_ranges(&[a.ranges(), b.ranges()].concat()));
+
+                // Inserting or removing the ranges one by one gives the same canonical sets
+                let (mut inserted, mut removed) = (a.clone(), a.clone());
+                for &(start, end) in b.ranges().iter().rev() {
+                    inserted.insert(start, end);
+                    removed.remove(start, end);
+                }
+                assert_eq!(inserted, a.union(b));
+                assert_eq!(removed, a.difference(b));
+            }
+        }
+    }
+
+    #[test]
+    fn full_domain() {
+        let all = RangeSet::from_ranges(&[(0, u64::MAX)]);
+        assert_eq!(all.cardinality(), 1 << 64);
+        assert!(all.complement(0, u64::MAX).is_empty());
+        assert_eq!(RangeSet::new().complement(0, u64::MAX), all);
+        assert_eq!(RangeSet::from_ranges(&[(5, u64::MAX)]).complement(0, u64::MAX).ranges(), &[(0, 4)]);
+        assert_eq!(RangeSet::from_ranges(&[(0, 10), (11, u64::MAX)]), all);
+        assert!(RangeSet::new().complement(5, 4).is_empty());
+
+        let mut set = all.clone();
+        set.remove(0, 0);
+        set.remove(u64::MAX, u64::MAX);
+        assert_eq!(set.ranges(), &[(1, u64::MAX - 1)]);
+        assert_eq!(all.difference(&set).cardinality(), 2);
+        set.insert(u64::MAX, u64::MAX);
+        set.insert(0, 0);
+        assert_eq!(set, all);
+
+        // Ranges with start after end are empty
+        set.remove(10, 5);
+        set.insert(10, 5);
+        assert_eq!(set, all);
+    }
+
+    #[test]
+    fn many_insertions() {
+        // Every other ID, inserted back to front, then the gaps are filled so everything merges into one range
+        let mut set = RangeSet::new();
+        for id in (0..10_000).rev() {
+            set.insert(2 * id, 2 * id);
+        }
+        assert_eq!(set.ranges().len(), 10_000);
+        for id in 0..9_999 {
+            set.insert(2 * id + 1, 2 * id + 1);
+        }
+        assert_eq!(set.ranges(), &[(0, 19_998)]);
+    }
+}